@@ -0,0 +1,167 @@
+use brands::{
+    Coin, CoinChallenge, Issuer, Params, PartialCoin, PrecomputedVerifier, Spender, SpentCoin,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+use diffie_hellman_groups::{MODPGroup, MODPGroup5};
+
+criterion_group!(
+    benches,
+    bench_withdraw,
+    bench_make_coin,
+    bench_verify,
+    bench_verify_reject,
+    bench_verify_precomputed,
+    bench_subgroup_membership,
+    bench_spend,
+    bench_reveal_identity
+);
+criterion_main!(benches);
+
+/// Creates an issuer and a spender who has registered with the issuer.
+fn setup_accounts(params: &Params) -> (Issuer, Spender) {
+    let issuer = Issuer::new(params.clone());
+    let mut spender = Spender::new(params.clone());
+    spender.set_registration_id(issuer.register(&spender.i).unwrap());
+    (issuer, spender)
+}
+
+/// Runs the withdrawal process and returns the coin with its partial coin for spending.
+fn withdraw_coin(issuer: &Issuer, spender: &Spender) -> (Coin, PartialCoin) {
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+    (coin, partial_coin)
+}
+
+/// Spends the coin twice upon different challenges.
+fn double_spend(spender: &Spender, coin: &Coin, partial_coin: &PartialCoin) -> [SpentCoin; 2] {
+    ["shopA", "shopB"].map(|message| {
+        let challenge = CoinChallenge::new(message.as_bytes(), coin);
+        spender.spend(coin.clone(), partial_coin.clone(), &challenge)
+    })
+}
+
+fn bench_withdraw(c: &mut Criterion) {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+
+    c.bench_function("withdraw", |b| {
+        b.iter_batched(
+            || issuer.setup_withdrawal_params(&spender.i).0,
+            |withdrawal_params| spender.withdraw(withdrawal_params).unwrap(),
+            criterion::BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_make_coin(c: &mut Criterion) {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let setup = || {
+        let (withdrawal_params, withdrawal_response_params) =
+            issuer.setup_withdrawal_params(&spender.i);
+        let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+        let withdrawal_response = issuer
+            .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+            .unwrap();
+        (withdrawal, withdrawal_response)
+    };
+
+    c.bench_function("make_coin", |b| {
+        b.iter_batched(
+            setup,
+            |(withdrawal, withdrawal_response)| spender.make_coin(withdrawal, withdrawal_response),
+            criterion::BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    c.bench_function("verify", |b| b.iter(|| coin.verify(&issuer.h, &params)));
+}
+
+fn bench_verify_reject(c: &mut Criterion) {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    // Replaces the first field (c1) of the coin bytes by 1, which makes a degenerate coin.
+    let bytes = coin.to_bytes();
+    let c1_len = u32::from_le_bytes(bytes[..4].try_into().unwrap()) as usize;
+    let mut degenerate_bytes = [1u32.to_le_bytes().as_slice(), &[1]].concat();
+    degenerate_bytes.extend_from_slice(&bytes[4 + c1_len..]);
+    let degenerate_coin = Coin::from_bytes(&degenerate_bytes).unwrap();
+
+    let mut group = c.benchmark_group("verify_reject");
+    group.bench_function("valid", |b| b.iter(|| coin.verify(&issuer.h, &params)));
+    group.bench_function("degenerate", |b| {
+        b.iter(|| degenerate_coin.verify(&issuer.h, &params))
+    });
+    group.finish();
+}
+
+fn bench_verify_precomputed(c: &mut Criterion) {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let coins: Vec<Coin> = (0..16)
+        .map(|_| withdraw_coin(&issuer, &spender).0)
+        .collect();
+    let verifier = PrecomputedVerifier::new(issuer.h.clone(), params.clone()).unwrap();
+
+    let mut group = c.benchmark_group("verify_batch_16");
+    group.bench_function("plain", |b| {
+        b.iter(|| coins.iter().all(|coin| coin.verify(&issuer.h, &params)))
+    });
+    group.bench_function("precomputed", |b| {
+        b.iter(|| coins.iter().all(|coin| verifier.verify(coin).is_ok()))
+    });
+    group.finish();
+}
+
+fn bench_subgroup_membership(c: &mut Criterion) {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, _) = setup_accounts(&params);
+    let (p, q) = (
+        MODPGroup5::prime_modulus(),
+        MODPGroup5::sophie_garmain_prime(),
+    );
+
+    let mut group = c.benchmark_group("subgroup_membership");
+    group.bench_function("modpow", |b| {
+        b.iter(|| brands::math::is_subgroup_element(&issuer.h, &q, &p))
+    });
+    group.bench_function("jacobi", |b| {
+        b.iter(|| params.is_quadratic_residue(&issuer.h))
+    });
+    group.finish();
+}
+
+fn bench_spend(c: &mut Criterion) {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+    let challenge = CoinChallenge::new("shopA".as_bytes(), &coin);
+
+    c.bench_function("spend", |b| {
+        b.iter(|| spender.spend(coin.clone(), partial_coin.clone(), &challenge))
+    });
+}
+
+fn bench_reveal_identity(c: &mut Criterion) {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+    let [spent_coin, spent_coin_2] = double_spend(&spender, &coin, &partial_coin);
+
+    c.bench_function("reveal_identity", |b| {
+        b.iter(|| spent_coin.reveal_identity(&spent_coin_2, &params).unwrap())
+    });
+}
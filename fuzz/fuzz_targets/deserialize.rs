@@ -0,0 +1,27 @@
+#![no_main]
+
+//! Feeds arbitrary bytes to the decoders of the crate, which must return an error on malformed
+//! input rather than panic.
+
+use std::str::FromStr;
+
+use brands::{Coin, CoinVerifier, Params, SpentCoin};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(coin) = Coin::from_bytes(data) {
+        assert!(Coin::from_bytes(&coin.to_bytes()).is_ok());
+    }
+    if let Ok(spent_coin) = SpentCoin::from_bytes(data) {
+        assert!(SpentCoin::from_bytes(&spent_coin.to_bytes()).is_ok());
+    }
+
+    if let Ok(s) = std::str::from_utf8(data) {
+        let _ = Coin::from_str(s);
+    }
+
+    let _ = serde_json::from_slice::<Coin>(data);
+    let _ = serde_json::from_slice::<SpentCoin>(data);
+    let _ = serde_json::from_slice::<Params>(data);
+    let _ = serde_json::from_slice::<CoinVerifier>(data);
+});
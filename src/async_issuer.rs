@@ -0,0 +1,65 @@
+//! Defines the asynchronous boundary of the issuer, for the issuers sitting behind a network or
+//! an HSM (hardware security module).
+
+use std::future::{ready, Future};
+
+use crate::{
+    Error, Identity, Issuer, RegistrationID, WithdrawalChallenge, WithdrawalParams,
+    WithdrawalResponse, WithdrawalResponseParams,
+};
+
+/// The operations of the issuer used by the spender, as the asynchronous counterparts of the
+/// methods of [Issuer], so that a service can await a remote issuer (e.g. signing by an HSM)
+/// instead of blocking on it.
+///
+/// The implementations may write the methods as `async fn`. The returned futures are [Send], so
+/// that they can be spawned on a multi-threaded runtime.
+pub trait AsyncIssuer {
+    /// Registers the spender as [Issuer::register] does.
+    fn register(&self, i: &Identity) -> impl Future<Output = Result<RegistrationID, Error>> + Send;
+
+    /// Sets up the parameters of a coin withdrawal as [Issuer::setup_withdrawal_params] does.
+    fn setup_withdrawal_params(
+        &self,
+        i: &Identity,
+    ) -> impl Future<Output = Result<(WithdrawalParams, WithdrawalResponseParams), Error>> + Send;
+
+    /// Responds to the challenge of a coin withdrawal as [Issuer::withdrawal_response] does.
+    fn withdrawal_response(
+        &self,
+        withdrawal: WithdrawalResponseParams,
+        challenge: &WithdrawalChallenge,
+    ) -> impl Future<Output = Result<WithdrawalResponse, Error>> + Send;
+}
+
+/// An [AsyncIssuer] which wraps an [Issuer] in process. The operations are computed on the
+/// calling thread when the methods are called, and the returned futures are ready immediately.
+pub struct BlockingIssuer(pub Issuer);
+
+impl From<Issuer> for BlockingIssuer {
+    fn from(issuer: Issuer) -> Self {
+        Self(issuer)
+    }
+}
+
+impl AsyncIssuer for BlockingIssuer {
+    fn register(&self, i: &Identity) -> impl Future<Output = Result<RegistrationID, Error>> + Send {
+        ready(self.0.register(i))
+    }
+
+    fn setup_withdrawal_params(
+        &self,
+        i: &Identity,
+    ) -> impl Future<Output = Result<(WithdrawalParams, WithdrawalResponseParams), Error>> + Send
+    {
+        ready(Ok(self.0.setup_withdrawal_params(i)))
+    }
+
+    fn withdrawal_response(
+        &self,
+        withdrawal: WithdrawalResponseParams,
+        challenge: &WithdrawalChallenge,
+    ) -> impl Future<Output = Result<WithdrawalResponse, Error>> + Send {
+        ready(self.0.withdrawal_response(withdrawal, challenge))
+    }
+}
@@ -4,19 +4,61 @@
 //! Those states include the state of a coin creation before coin withdrawal ([PartialCoin]),
 //! before being spent ([Coin]), and after spent ([SpentCoin]).
 
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    io::Read,
+    str::FromStr,
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+use subtle::{Choice, ConstantTimeEq};
 
-use crate::{cryptographics::hash_to_number, params::Params, Identity, Withdrawal};
+use crate::{
+    cryptographics::{
+        ct_eq_biguint, ct_eq_bytes, hash_to_number, hash_to_numbers, hash_to_scalar, read_hmac_key,
+    },
+    encoding::{
+        biguint_encoded_len, write_biguint, write_biguint_in, write_bytes_in, Endianness, Reader,
+    },
+    math::{modinv_checked, sub_mod},
+    onetime::ONETIME_KEY_TAG,
+    params::Params,
+    proof::CoinProof,
+    research::ResearchRecord,
+    telemetry::{self, Timer},
+    transcript::SpendTranscript,
+    Error, Identity, VerifyError, Withdrawal,
+};
 
 /// A mathematic representation of a "coin" which has not yet complete its creation
 /// during coin withdrawal process.
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartialCoin {
     pub(crate) s: BigUint,
     pub(crate) x1: BigUint,
     pub(crate) x2: BigUint,
     pub(crate) u: BigUint,
     pub(crate) v: BigUint,
+    /// The values (x1, x2) of the additional show slots of a k-show coin.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub(crate) x_extra: Vec<(BigUint, BigUint)>,
+}
+
+impl PartialCoin {
+    /// Returns the values (x1, x2) of the show slot, or None if the slot is not provisioned.
+    pub(crate) fn slot_secrets(&self, slot: u32) -> Option<(&BigUint, &BigUint)> {
+        match slot {
+            0 => Some((&self.x1, &self.x2)),
+            _ => self.x_extra.get(slot as usize - 1).map(|(x1, x2)| (x1, x2)),
+        }
+    }
 }
 
 impl From<Withdrawal> for PartialCoin {
@@ -30,6 +72,7 @@ impl From<Withdrawal> for PartialCoin {
 
 /// A mathematic representation of a "coin" which is ready to be spent.
 #[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coin {
     pub(crate) c1: BigUint,
     pub(crate) c2: BigUint,
@@ -38,73 +81,936 @@ pub struct Coin {
     pub(crate) c5: BigUint,
     pub(crate) c6: BigUint,
     pub(crate) cd: BigUint,
+    /// The metadata bound into `cd`, if any.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) metadata: Option<CoinMetadata>,
+    /// The commitments (i.e. `c2`) of the additional show slots of a k-show coin.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub(crate) c2_extra: Vec<BigUint>,
+}
+
+/// Metadata of a coin (e.g. denomination) which is chosen by the spender in withdrawal process,
+/// by calling the method [withdraw_with_metadata](crate::Spender::withdraw_with_metadata). It is
+/// bound into the coin, so that it cannot be altered without invalidating the coin.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoinMetadata {
+    /// The denomination of the coin.
+    pub denomination: u64,
+    /// Application-defined attributes of the coin.
+    pub attributes: Vec<u8>,
+}
+
+impl CoinMetadata {
+    pub fn new(denomination: u64, attributes: Vec<u8>) -> Self {
+        Self {
+            denomination,
+            attributes,
+        }
+    }
+
+    /// Creates the metadata whose attributes carry the pseudonym of the spender, which can be
+    /// read back from the coin by [Coin::bound_pseudonym], e.g. for a blacklist of pseudonyms.
+    ///
+    /// The pseudonym is chosen by the spender in the withdrawal and is not seen by the issuer,
+    /// who signs the coin blindly. It therefore identifies a spender only as far as the spender
+    /// is obliged to use an assigned pseudonym (e.g. by the terms of the service), while the
+    /// coins of the spender carrying the same pseudonym are linkable to each other.
+    pub fn with_pseudonym(denomination: u64, pseudonym: &BigUint) -> Self {
+        let mut attributes = PSEUDONYM_TAG.to_vec();
+        write_biguint(&mut attributes, pseudonym);
+        Self::new(denomination, attributes)
+    }
+
+    /// Returns the pseudonym carried by the attributes created by
+    /// [CoinMetadata::with_pseudonym], or None if the attributes do not carry one.
+    pub fn pseudonym(&self) -> Option<BigUint> {
+        let bytes = self.attributes.strip_prefix(PSEUDONYM_TAG)?;
+        let mut reader = Reader::new(bytes);
+        let pseudonym = reader.read_biguint().ok()?;
+        reader.finish().ok()?;
+        Some(pseudonym)
+    }
+
+    /// Encodes the metadata into bytes in the byte order `endianness`, which are the input of
+    /// the hash of a coin.
+    pub(crate) fn to_bytes(&self, endianness: Endianness) -> Vec<u8> {
+        let mut buf = endianness.u64_to_bytes(self.denomination).to_vec();
+        write_bytes_in(&mut buf, &self.attributes, endianness);
+        buf
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, Error> {
+        Ok(Self {
+            denomination: reader.read_u64()?,
+            attributes: reader.read_bytes()?.to_vec(),
+        })
+    }
+}
+
+/// Prefix of the attributes of the metadata which carry the pseudonym of the spender.
+const PSEUDONYM_TAG: &[u8] = b"brands-rs/pseudonym";
+
+/// Domain tag of the coin challenges derived from transaction ids.
+const TXID_TAG: &[u8] = b"brands-rs/txid";
+
+/// Computes the challenge of a coin, cd = Hash(c1,c2,c3,c4,c5,metadata) (mod q), which is used
+/// in both coin withdrawal and verification. `c2` contains the commitments of all show slots,
+/// which is a single value for a coin that can be shown once. The values are hashed in the byte
+/// order of [Params::endianness].
+pub(crate) fn coin_challenge_d(
+    params: &Params,
+    c1: &BigUint,
+    c2: &[&BigUint],
+    c3: &BigUint,
+    c4: &BigUint,
+    c5: &BigUint,
+    metadata: Option<&CoinMetadata>,
+) -> BigUint {
+    let endianness = params.endianness();
+    let mut data = vec![endianness.biguint_to_bytes(c1)];
+    data.extend(c2.iter().map(|c2| endianness.biguint_to_bytes(c2)));
+    data.extend([c3, c4, c5].map(|x| endianness.biguint_to_bytes(x)));
+    if let Some(metadata) = metadata {
+        data.push(metadata.to_bytes(endianness));
+    }
+    hash_to_scalar(&params.hmac_key(), &data, &params.q)
+}
+
+/// The format version of a [Coin], which determines the range of the challenge `cd` accepted
+/// in verification.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CoinFormat {
+    /// The legacy format of the coins created before the coin challenge was hashed into a
+    /// scalar under the derived HMac key, where `cd` is the HMac of the fields under the scheme
+    /// key reduced mod p, i.e. in the range `[0, p)`. The coins in the current format are
+    /// accepted in this format as well.
+    V1,
+    /// The current format, where `cd` is reduced mod q, i.e. in the range `[0, q)`.
+    #[default]
+    V2,
+}
+
+/// A [Coin] bundled with the [PartialCoin] that the spender needs for spending it, so that
+/// the two are not lost track of each other.
+///
+/// The bundle should be kept by the spender. Only the [Coin] is shared with receivers.
+///
+/// The crate has no wallet type. A spender persists its coins by serializing the bundles
+/// (together with the [Withdrawal]s still pending a response), which contain the secrets of the
+/// coins and should be stored privately.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoinWithSecrets {
+    /// The coin which is ready to be spent.
+    pub coin: Coin,
+    pub(crate) partial_coin: PartialCoin,
 }
 
 /// A challenge created by coin receiver. The spender needs to give a response upon
 /// receiving this chanllenge in order to prove the ownership of the coin.
+///
+/// The challenge can be persisted by the receiver alongside the [SpentCoin], e.g. for
+/// settling a dispute later.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CoinChallenge(pub(crate) BigUint);
 
+/// Encodes the commitments of a coin which are hashed into its challenges, in the byte order
+/// `endianness`.
+fn challenge_inputs(coin: &Coin, endianness: Endianness) -> [Vec<u8>; 2] {
+    [&coin.c1, &coin.c2].map(|x| endianness.biguint_to_bytes(x))
+}
+
 impl CoinChallenge {
     pub fn new(message: &[u8], coin: &Coin) -> Self {
+        Self::new_with_endianness(message, coin, Endianness::Little)
+    }
+
+    /// Creates a challenge as [CoinChallenge::new] does, with the commitments of the coin hashed
+    /// in the byte order `endianness`, which should be the one of [Params::endianness].
+    pub fn new_with_endianness(message: &[u8], coin: &Coin, endianness: Endianness) -> Self {
+        Self(hash_to_number(message, &challenge_inputs(coin, endianness)))
+    }
+
+    /// Creates a challenge as [CoinChallenge::new] does, but rejects an empty message.
+    ///
+    /// The message is what binds the spent coin to the payment (e.g. the merchant and the
+    /// invoice). The challenge of an empty message is bound only to the coin, so the spent coin
+    /// cannot be attributed to any payment in a dispute, and the same challenge is derived by
+    /// every receiver of the coin.
+    ///
+    /// Returns [Error::EmptyMessage] if the message is empty.
+    pub fn new_checked(message: &[u8], coin: &Coin) -> Result<Self, Error> {
+        if message.is_empty() {
+            return Err(Error::EmptyMessage);
+        }
+        Ok(Self::new(message, coin))
+    }
+
+    /// Creates the challenges of the coins (e.g. a cart of coins paid together) upon the same
+    /// message, which are equal to the ones created by [CoinChallenge::new] for each coin. The
+    /// keyed hash state of the message is set up once for the batch.
+    pub fn new_batch(message: &[u8], coins: &[Coin]) -> Vec<Self> {
+        Self::new_batch_with_endianness(message, coins, Endianness::Little)
+    }
+
+    /// Creates the challenges as [CoinChallenge::new_batch] does, in the byte order
+    /// `endianness`, which are equal to the ones created by
+    /// [CoinChallenge::new_with_endianness] for each coin.
+    pub fn new_batch_with_endianness(
+        message: &[u8],
+        coins: &[Coin],
+        endianness: Endianness,
+    ) -> Vec<Self> {
+        hash_to_numbers(
+            message,
+            coins.iter().map(|coin| challenge_inputs(coin, endianness)),
+        )
+        .into_iter()
+        .map(Self)
+        .collect()
+    }
+
+    /// Creates a challenge derived solely from the transaction id of the merchant and the coin,
+    /// which is domain separated from the challenges created by [CoinChallenge::new].
+    ///
+    /// Retrying a payment with the same transaction id yields the same challenge, so the coin
+    /// spent again upon the retry has the same responses, which is a duplicate rather than a
+    /// double spending (see [DepositOutcome::Duplicate](crate::DepositOutcome::Duplicate)).
+    /// Therefore a transaction id must not be reused for different payments.
+    pub fn from_txid(txid: &[u8; 32], coin: &Coin) -> Self {
+        Self::from_txid_with_endianness(txid, coin, Endianness::Little)
+    }
+
+    /// Creates a challenge as [CoinChallenge::from_txid] does, in the byte order `endianness`.
+    pub fn from_txid_with_endianness(txid: &[u8; 32], coin: &Coin, endianness: Endianness) -> Self {
+        let [c1, c2] = challenge_inputs(coin, endianness);
+        Self(hash_to_number(TXID_TAG, &[txid.to_vec(), c1, c2]))
+    }
+
+    /// Creates a challenge as [CoinChallenge::new] does, with the message read from the stream
+    /// (e.g. a large invoice document), so that the message is not buffered in memory. Returns
+    /// the error of reading the stream.
+    pub fn new_from_reader(reader: impl Read, coin: &Coin) -> std::io::Result<Self> {
+        Self::new_from_reader_with_endianness(reader, coin, Endianness::Little)
+    }
+
+    /// Creates a challenge as [CoinChallenge::new_from_reader] does, in the byte order
+    /// `endianness`.
+    pub fn new_from_reader_with_endianness(
+        reader: impl Read,
+        coin: &Coin,
+        endianness: Endianness,
+    ) -> std::io::Result<Self> {
+        let key = read_hmac_key(reader)?;
+        Ok(Self(hash_to_number(
+            &key,
+            &challenge_inputs(coin, endianness),
+        )))
+    }
+
+    /// Creates a challenge which, in addition to the message and the coin, also commits to the
+    /// identity of the spender. The spent coin should be verified by [SpentCoin::verify_bound]
+    /// with the identity claimed by the spender.
+    ///
+    /// ## Anonymity
+    /// Binding the identity requires the receiver to learn the identity of the spender, which
+    /// gives up the anonymity of the payment toward the receiver. It is only suitable when the
+    /// spender is identified to the receiver anyway.
+    pub fn new_bound(message: &[u8], coin: &Coin, spender_identity: &Identity) -> Self {
+        Self::new_bound_with_endianness(message, coin, spender_identity, Endianness::Little)
+    }
+
+    /// Creates a challenge as [CoinChallenge::new_bound] does, in the byte order `endianness`.
+    pub fn new_bound_with_endianness(
+        message: &[u8],
+        coin: &Coin,
+        spender_identity: &Identity,
+        endianness: Endianness,
+    ) -> Self {
+        let [c1, c2] = challenge_inputs(coin, endianness);
+        let identity = endianness.biguint_to_bytes(spender_identity);
+        Self(hash_to_number(message, &[c1, c2, identity]))
+    }
+
+    /// Creates a challenge which, in addition to the message and the coin, also commits to the
+    /// one-time public key of the coin returned by
+    /// [onetime_public_key](crate::Spender::onetime_public_key). The spender can then sign the
+    /// transaction by [sign_transaction](crate::Spender::sign_transaction), which the receiver
+    /// verifies by [TransactionSignature::verify](crate::TransactionSignature::verify) with the
+    /// same one-time public key.
+    pub fn new_with_onetime_key(message: &[u8], coin: &Coin, onetime_pk: &BigUint) -> Self {
+        Self::new_with_onetime_key_with_endianness(message, coin, onetime_pk, Endianness::Little)
+    }
+
+    /// Creates a challenge as [CoinChallenge::new_with_onetime_key] does, in the byte order
+    /// `endianness`.
+    pub fn new_with_onetime_key_with_endianness(
+        message: &[u8],
+        coin: &Coin,
+        onetime_pk: &BigUint,
+        endianness: Endianness,
+    ) -> Self {
+        let [c1, c2] = challenge_inputs(coin, endianness);
+        let onetime_pk = endianness.biguint_to_bytes(onetime_pk);
         Self(hash_to_number(
             message,
-            &[coin.c1.to_bytes_le(), coin.c2.to_bytes_le()],
+            &[c1, c2, ONETIME_KEY_TAG.to_vec(), onetime_pk],
         ))
     }
+
+    /// Encodes the challenge into bytes, which can be decoded by [CoinChallenge::from_bytes].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with_endianness(Endianness::Little)
+    }
+
+    /// Encodes the challenge as [CoinChallenge::to_bytes] does, in the byte order `endianness`.
+    /// The bytes can be decoded by [CoinChallenge::from_bytes_with_endianness] with the same
+    /// byte order.
+    pub fn to_bytes_with_endianness(&self, endianness: Endianness) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_biguint_in(&mut buf, &self.0, 0, endianness);
+        buf
+    }
+
+    /// Decodes a challenge from the bytes encoded by [CoinChallenge::to_bytes]. Returns
+    /// [Error::DeserializationFailed] if the bytes are malformed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes_with_endianness(bytes, Endianness::Little)
+    }
+
+    /// Decodes a challenge from the bytes encoded by [CoinChallenge::to_bytes_with_endianness]
+    /// in the byte order `endianness`. Returns [Error::DeserializationFailed] if the bytes are
+    /// malformed.
+    pub fn from_bytes_with_endianness(bytes: &[u8], endianness: Endianness) -> Result<Self, Error> {
+        let mut reader = Reader::with_endianness(bytes, endianness);
+        let challenge = Self(reader.read_biguint()?);
+        reader.finish()?;
+        Ok(challenge)
+    }
+
+    /// Creates a challenge whose value is computed by the function `f` from the coin, e.g. for
+    /// incorporating external context (such as timestamp and merchant id) into the challenge.
+    /// The challenge can be used for spending the coin as the one created by [CoinChallenge::new].
+    ///
+    /// Returns [Error::InvalidChallenge] if the computed value is not in the range `[0, q)`.
+    pub fn new_with(
+        coin: &Coin,
+        params: &Params,
+        f: impl FnOnce(&Coin) -> BigUint,
+    ) -> Result<Self, Error> {
+        let d = f(coin);
+        if d >= params.q {
+            return Err(Error::InvalidChallenge);
+        }
+        Ok(Self(d))
+    }
 }
 
+/// A fingerprint which identifies a [Coin], computed by the method [fingerprint](crate::Coin::fingerprint).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoinFingerprint(pub [u8; 32]);
+
+/// A warning of the structure of a coin returned by [Coin::structural_warnings].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CoinWarning {
+    /// The field (e.g. `c1`, named as in [Coin::FIELD_COUNT]) has far fewer bits than expected
+    /// of a uniformly random value of its range, i.e. the bits of `p` for the group elements
+    /// `c1` to `c5`, and the bits of `q` for the exponents `c6` and `cd`.
+    ShortField {
+        field: &'static str,
+        bits: u64,
+        expected_bits: u64,
+    },
+}
+
+/// Number of bits by which a field of a coin is shorter than expected for
+/// [CoinWarning::ShortField]. A uniformly random value is that short with probability 2^-64.
+const SHORT_FIELD_MARGIN_BITS: u64 = 64;
+
+/// Names of the fields of a coin in the order of [Coin::fields].
+const FIELD_NAMES: [&str; Coin::FIELD_COUNT] = ["c1", "c2", "c3", "c4", "c5", "c6", "cd"];
+
+/// Prefix of the string form of a [Coin], which identifies the version of the format.
+const COIN_TOKEN_PREFIX: &str = "brands-coin-v1:";
+
+/// Length of the checksum appended to the encoded coin in its string form.
+const COIN_TOKEN_CHECKSUM_LEN: usize = 4;
+
+/// Marker preceding the encoding of a k-show coin. It is not a valid length prefix of `c1`, so
+/// that the encoding of a coin that can be shown once is unchanged.
+pub(crate) const K_SHOW_MARKER: u32 = u32::MAX;
+
 impl Coin {
+    /// Number of the field elements of a coin returned by [Coin::fields]. They are, in order,
+    ///
+    /// - `c1 = (i * g2)^s`, the blinded identity of the spender,
+    /// - `c2 = g1^x1 * g2^x2`, the commitment of the first show slot,
+    /// - `c3 = z^s`, the blinded registration ID of the spender,
+    /// - `c4` and `c5`, the blinded commitments `a` and `b` of the issuer's signature,
+    /// - `c6`, the blinded response of the issuer's signature,
+    /// - `cd`, the challenge of the signature hashed from the other fields.
+    ///
+    /// The coin is valid if `c4 * h^cd = g^c6` and `c5 * c3^cd = c1^c6`.
+    pub const FIELD_COUNT: usize = 7;
+
+    /// Returns the field elements of the coin in the order documented in [Coin::FIELD_COUNT],
+    /// e.g. for hashing or serializing the coin generically. The commitments of the additional
+    /// show slots of a k-show coin and the metadata are not included.
+    pub fn fields(&self) -> [&BigUint; Self::FIELD_COUNT] {
+        [
+            &self.c1, &self.c2, &self.c3, &self.c4, &self.c5, &self.c6, &self.cd,
+        ]
+    }
+
+    /// Creates a coin from the field elements in the order documented in [Coin::FIELD_COUNT],
+    /// e.g. for a decoder other than [Coin::from_bytes]. The coin carries no metadata and can
+    /// be shown once, so that `Coin::from_fields(coin.fields().map(Clone::clone))` reproduces
+    /// such a coin.
+    ///
+    /// The coin is not verified. Call [Coin::verify] before accepting it.
+    pub fn from_fields(fields: [BigUint; Self::FIELD_COUNT]) -> Self {
+        let [c1, c2, c3, c4, c5, c6, cd] = fields;
+        Self {
+            c1,
+            c2,
+            c3,
+            c4,
+            c5,
+            c6,
+            cd,
+            metadata: None,
+            c2_extra: Vec::new(),
+        }
+    }
+
+    /// Returns the compact proof of validity of the coin, which can be verified by
+    /// [CoinProof::verify] instead of the coin, e.g. by a receiver with constrained bandwidth.
+    pub fn proof(&self) -> CoinProof {
+        CoinProof {
+            c1: self.c1.clone(),
+            c2: self.c2.clone(),
+            c3: self.c3.clone(),
+            c6: self.c6.clone(),
+            cd: self.cd.clone(),
+            metadata: self.metadata.clone(),
+            c2_extra: self.c2_extra.clone(),
+        }
+    }
+
+    /// Returns the warnings of the structure of the coin which may make it distinguishable from
+    /// the other coins of the issuer, e.g. a field far shorter than a random value, which hints
+    /// that the coin was withdrawn by a spender with a non-random generator. A receiver
+    /// concerned with privacy can use it to gauge whether the coin stands out in its anonymity
+    /// set.
+    ///
+    /// This is a heuristic for privacy tooling rather than a security guarantee: a coin without
+    /// warnings may still be linkable, and the validity of the coin is checked only by
+    /// [Coin::verify].
+    pub fn structural_warnings(&self, params: &Params) -> Vec<CoinWarning> {
+        let (p_bits, q_bits) = (params.p.bits(), params.q.bits());
+        self.fields()
+            .into_iter()
+            .zip(FIELD_NAMES)
+            .enumerate()
+            .filter_map(|(index, (value, field))| {
+                // c6 and cd are exponents (mod q), and the others are group elements (mod p).
+                let expected_bits = if index < 5 { p_bits } else { q_bits };
+                let bits = value.bits();
+                (bits + SHORT_FIELD_MARGIN_BITS < expected_bits).then_some(
+                    CoinWarning::ShortField {
+                        field,
+                        bits,
+                        expected_bits,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the anonymized record of the coin, which contains the bit lengths of the fields
+    /// and the fingerprint of the coin but not the values of the fields, e.g. for publishing a
+    /// dataset of coins for research.
+    pub fn to_research_record(&self) -> ResearchRecord {
+        let [c1, c2, c3, c4, c5, c6, cd] = self.fields().map(BigUint::bits);
+        ResearchRecord {
+            fingerprint: self
+                .fingerprint()
+                .0
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect(),
+            c1_bits: c1,
+            c2_bits: c2,
+            c3_bits: c3,
+            c4_bits: c4,
+            c5_bits: c5,
+            c6_bits: c6,
+            cd_bits: cd,
+            shows: self.shows(),
+            has_metadata: self.metadata.is_some(),
+        }
+    }
+
+    /// Encodes the coin into bytes, followed by the metadata if the coin carries one. The coin
+    /// can be decoded by [Coin::from_bytes].
+    ///
+    /// A k-show coin is prefixed with a marker and the commitments of its additional show slots.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode(0, 0, Endianness::Little)
+    }
+
+    /// Encodes the coin into bytes as [Coin::to_bytes] does, with the integers in the byte
+    /// order `endianness`, e.g. for an implementation using big-endian. The coin can be decoded
+    /// by [Coin::from_bytes_with_endianness] with the same byte order.
+    pub fn to_bytes_with_endianness(&self, endianness: Endianness) -> Vec<u8> {
+        self.encode(0, 0, endianness)
+    }
+
+    /// Encodes the coin into bytes as [Coin::to_bytes] does, with the group elements and the
+    /// scalars padded to [Params::serialized_element_len] and [Params::serialized_scalar_len]
+    /// respectively. The coins in the same group (with the same metadata and number of shows)
+    /// are therefore encoded into the same length. The coin can be decoded by [Coin::from_bytes].
+    pub fn to_padded_bytes(&self, params: &Params) -> Vec<u8> {
+        self.encode(
+            params.serialized_element_len(),
+            params.serialized_scalar_len(),
+            Endianness::Little,
+        )
+    }
+
+    /// Returns the length of the bytes encoded by [Coin::to_bytes], without encoding the coin,
+    /// e.g. for choosing the version of a QR code to carry the coin.
+    ///
+    /// The length does not depend on the group. The coins encoded by [Coin::to_padded_bytes]
+    /// have the same length in the same group instead.
+    pub fn serialized_len(&self) -> usize {
+        let mut len = 0;
+        if !self.c2_extra.is_empty() {
+            // marker and number of the additional slots
+            len += 8 + self.c2_extra.iter().map(biguint_encoded_len).sum::<usize>();
+        }
+        len += [
+            &self.c1, &self.c2, &self.c3, &self.c4, &self.c5, &self.c6, &self.cd,
+        ]
+        .into_iter()
+        .map(biguint_encoded_len)
+        .sum::<usize>();
+        if let Some(metadata) = &self.metadata {
+            // denomination and length-prefixed attributes
+            len += 12 + metadata.attributes.len();
+        }
+        len
+    }
+
+    /// Encodes the coin with the group elements and the scalars padded to the lengths, in the
+    /// byte order.
+    fn encode(&self, element_len: usize, scalar_len: usize, endianness: Endianness) -> Vec<u8> {
+        let mut buf = Vec::new();
+        if !self.c2_extra.is_empty() {
+            buf.extend_from_slice(&endianness.u32_to_bytes(K_SHOW_MARKER));
+            buf.extend_from_slice(&endianness.u32_to_bytes(self.c2_extra.len() as u32));
+            for x in &self.c2_extra {
+                write_biguint_in(&mut buf, x, element_len, endianness);
+            }
+        }
+        for x in [&self.c1, &self.c2, &self.c3, &self.c4, &self.c5] {
+            write_biguint_in(&mut buf, x, element_len, endianness);
+        }
+        for x in [&self.c6, &self.cd] {
+            write_biguint_in(&mut buf, x, scalar_len, endianness);
+        }
+        if let Some(metadata) = &self.metadata {
+            buf.extend_from_slice(&metadata.to_bytes(endianness));
+        }
+        buf
+    }
+
+    /// Decodes a coin from the bytes encoded by [Coin::to_bytes]. Returns
+    /// [Error::DeserializationFailed] if the bytes are malformed, or a field is declared longer
+    /// than 4096 bytes.
+    ///
+    /// The coin is not verified. Call [Coin::verify] before accepting it.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes_with_endianness(bytes, Endianness::Little)
+    }
+
+    /// Decodes only the first field `c1` of the coin from the bytes encoded by [Coin::to_bytes],
+    /// skipping the commitments of a k-show coin without decoding them, e.g. for rejecting a
+    /// degenerate coin (i.e. `c1 = 1`) before decoding the rest of it. Returns
+    /// [Error::DeserializationFailed] if `c1` cannot be decoded. The rest of the bytes is not
+    /// checked.
+    pub fn peek_c1(bytes: &[u8]) -> Result<BigUint, Error> {
+        Self::peek_c1_with_endianness(bytes, Endianness::Little)
+    }
+
+    /// Decodes only the first field `c1` of the coin, as [Coin::peek_c1] does, from the bytes
+    /// encoded by [Coin::to_bytes_with_endianness] in the byte order `endianness`.
+    pub fn peek_c1_with_endianness(bytes: &[u8], endianness: Endianness) -> Result<BigUint, Error> {
+        let mut reader = Reader::with_endianness(bytes, endianness);
+        if bytes.starts_with(&endianness.u32_to_bytes(K_SHOW_MARKER)) {
+            reader.read_u32()?;
+            for _ in 0..reader.read_u32()? {
+                reader.read_bytes()?;
+            }
+        }
+        reader.read_biguint()
+    }
+
+    /// Decodes a coin from the bytes encoded by [Coin::to_bytes_with_endianness] in the byte
+    /// order `endianness`, as [Coin::from_bytes] does.
+    pub fn from_bytes_with_endianness(bytes: &[u8], endianness: Endianness) -> Result<Self, Error> {
+        let mut reader = Reader::with_endianness(bytes, endianness);
+        let mut c2_extra = Vec::new();
+        if bytes.starts_with(&endianness.u32_to_bytes(K_SHOW_MARKER)) {
+            reader.read_u32()?;
+            for _ in 0..reader.read_u32()? {
+                c2_extra.push(reader.read_biguint()?);
+            }
+        }
+        let mut coin = Self {
+            c1: reader.read_biguint()?,
+            c2: reader.read_biguint()?,
+            c3: reader.read_biguint()?,
+            c4: reader.read_biguint()?,
+            c5: reader.read_biguint()?,
+            c6: reader.read_biguint()?,
+            cd: reader.read_biguint()?,
+            metadata: None,
+            c2_extra,
+        };
+        if !reader.is_empty() {
+            coin.metadata = Some(CoinMetadata::read(&mut reader)?);
+        }
+        reader.finish()?;
+        Ok(coin)
+    }
+
+    /// Returns the number of times that the coin can be shown (i.e. spent upon different
+    /// challenges) before the identity of the spender can be revealed. It is `k` for a k-show
+    /// coin withdrawn by [withdraw_k_show](crate::Spender::withdraw_k_show), or 1 otherwise.
+    pub fn shows(&self) -> u32 {
+        1 + self.c2_extra.len() as u32
+    }
+
+    /// Returns the commitment (i.e. `c2`) of the show slot, or None if the slot is not
+    /// provisioned.
+    pub(crate) fn slot_commitment(&self, slot: u32) -> Option<&BigUint> {
+        match slot {
+            0 => Some(&self.c2),
+            _ => self.c2_extra.get(slot as usize - 1),
+        }
+    }
+
+    /// Returns the pseudonym of the spender bound into the metadata of the coin by
+    /// [CoinMetadata::with_pseudonym], or None if the coin carries none.
+    ///
+    /// The identity of the spender (`i`) cannot be recovered from a coin unless the coin is
+    /// double spent, so that the honest spenders remain unlinkable. A bound pseudonym is the
+    /// only identifier a coin can expose, e.g. for blacklisting a spender, and it is trustworthy
+    /// only after the coin is verified (e.g. by [Coin::verify]), as the metadata is bound by
+    /// the signature of the issuer.
+    pub fn bound_pseudonym(&self) -> Option<BigUint> {
+        self.metadata.as_ref()?.pseudonym()
+    }
+
+    /// Returns the fingerprint of the coin, which is the SHA-256 hash of the coin bytes.
+    pub fn fingerprint(&self) -> CoinFingerprint {
+        CoinFingerprint(Sha256::digest(self.to_bytes()).into())
+    }
+
     /// Verifies if the coin is valid by using the issuer's identity (h) and the
     /// publicly known parameters. Returns true if the coin is valid.
     pub fn verify(&self, h: &Identity, params: &Params) -> bool {
-        if self.c1 == BigUint::from(1u64) {
-            return false;
+        let timer = Timer::start();
+        let valid = self.check(h, params).is_ok();
+        timer.record(telemetry::VERIFY_DURATION);
+        valid
+    }
+
+    /// Verifies the coin as [Coin::verify] does, and checks that the metadata bound into the
+    /// coin is the claimed one. Returns the validated metadata.
+    ///
+    /// Returns [VerifyError::MetadataMismatch] if the coin does not carry the claimed metadata.
+    pub fn verify_and_extract(
+        &self,
+        h: &Identity,
+        params: &Params,
+        claimed_metadata: &CoinMetadata,
+    ) -> Result<CoinMetadata, VerifyError> {
+        if self.metadata.as_ref() != Some(claimed_metadata) {
+            return Err(VerifyError::MetadataMismatch);
         }
+        self.check(h, params)?;
+        Ok(claimed_metadata.clone())
+    }
 
-        let ver_cd = hash_to_number(
-            params.scheme_key.as_bytes(),
-            &[
-                self.c1.to_bytes_le(),
-                self.c2.to_bytes_le(),
-                self.c3.to_bytes_le(),
-                self.c4.to_bytes_le(),
-                self.c5.to_bytes_le(),
-            ],
-        ) % &params.p;
+    /// Verifies the coins as [Coin::verify] does. Returns true for each valid coin, in the
+    /// order of the coins.
+    pub fn verify_batch(coins: &[Coin], h: &Identity, params: &Params) -> Vec<bool> {
+        Self::verify_batch_detailed(coins, h, params)
+            .iter()
+            .map(Result::is_ok)
+            .collect()
+    }
 
-        if self.cd != ver_cd {
-            // println!("Coin::verify cd != ver_cd");
-            return false;
+    /// Verifies the coins as [Coin::verify_batch] does, but returns the reason of the failure
+    /// for each coin, e.g. for logging why the coins are rejected. The issuer's identity (h) is
+    /// checked once for the whole batch.
+    pub fn verify_batch_detailed(
+        coins: &[Coin],
+        h: &Identity,
+        params: &Params,
+    ) -> Vec<Result<(), VerifyError>> {
+        if let Err(err) = check_issuer_key(h, params) {
+            return vec![Err(err); coins.len()];
+        }
+        coins
+            .iter()
+            .map(|coin| coin.check_with(params, CoinFormat::V2, |e| h.modpow(e, &params.p)))
+            .collect()
+    }
+
+    /// Verifies the coin as [Coin::verify] does, but returns the reason of the failure.
+    pub(crate) fn check(&self, h: &Identity, params: &Params) -> Result<(), VerifyError> {
+        let cd = self.check_structure(params, CoinFormat::V2)?;
+        check_issuer_key(h, params)?;
+        self.check_equations(params, CoinFormat::V2, &cd, |e| h.modpow(e, &params.p))
+    }
+
+    /// Returns the format version of the coin, which is [CoinFormat::V1] if the challenge `cd`
+    /// is not reduced mod q (i.e. `cd >= q`) or is the legacy challenge of the coin, or
+    /// [CoinFormat::V2] otherwise.
+    pub fn format_version(&self, params: &Params) -> CoinFormat {
+        if self.cd >= params.q || self.is_legacy_challenge(params) {
+            CoinFormat::V1
+        } else {
+            CoinFormat::V2
+        }
+    }
+
+    /// Verifies the coin as [Coin::verify] does, accepting the coins in the format `format`,
+    /// e.g. for accepting the legacy coins during a migration by [CoinFormat::V1]. Returns the
+    /// reason of the failure.
+    ///
+    /// Returns [VerifyError::LegacyFormat] if `format` is [CoinFormat::V2] and the coin is in
+    /// the legacy format.
+    pub fn verify_format(
+        &self,
+        h: &Identity,
+        params: &Params,
+        format: CoinFormat,
+    ) -> Result<(), VerifyError> {
+        let cd = self.check_structure(params, format)?;
+        check_issuer_key(h, params)?;
+        self.check_equations(params, format, &cd, |e| h.modpow(e, &params.p))
+    }
+
+    /// Verifies the coin against the issuer's identity (h), which is assumed to be checked by
+    /// [check_issuer_key], where `h_pow` computes `h^e (mod p)`.
+    pub(crate) fn check_with(
+        &self,
+        params: &Params,
+        format: CoinFormat,
+        h_pow: impl FnOnce(&BigUint) -> BigUint,
+    ) -> Result<(), VerifyError> {
+        let cd = self.check_structure(params, format)?;
+        self.check_equations(params, format, &cd, h_pow)
+    }
+
+    /// Runs the cheap checks of the coin which need neither hashing nor modular
+    /// exponentiation, so that an obviously invalid coin is rejected before the expensive
+    /// checks. Returns `cd` reduced as the format `format` requires.
+    fn check_structure(&self, params: &Params, format: CoinFormat) -> Result<BigUint, VerifyError> {
+        let one = BigUint::from(1u64);
+        // c1 = (i * g2)^s = 1 means the identity of the spender is not bound into the coin,
+        // so that the spent coin cannot be traced to the spender.
+        if self.c1 == one {
+            return Err(VerifyError::DegenerateCoin);
+        }
+        // c2 = g1^x1 * g2^x2 = 1 means the commitment of a show slot does not blind the
+        // responses, which can then be computed without knowing the secrets of the coin.
+        if std::iter::once(&self.c2)
+            .chain(&self.c2_extra)
+            .any(|c2| *c2 == one)
+        {
+            return Err(VerifyError::DegenerateCoin);
+        }
+        // c3 = z^s = 1 means the registration of the spender collapsed (e.g. s = 0), so that
+        // the coin is not linked to a registered spender.
+        if self.c3 == one {
+            return Err(VerifyError::DegenerateCoin);
+        }
+
+        // The legacy coins may carry cd reduced mod p. The value reduced mod q is used in the
+        // verification equations, while the challenge is compared unreduced, see
+        // check_equations.
+        let cd = match format {
+            CoinFormat::V1 if self.cd < params.p => &self.cd % &params.q,
+            CoinFormat::V1 => return Err(VerifyError::ChallengeMismatch),
+            CoinFormat::V2 if self.cd >= params.q => return Err(VerifyError::LegacyFormat),
+            CoinFormat::V2 => self.cd.clone(),
+        };
+        Ok(cd)
+    }
+
+    /// Checks the challenge and the signature of the issuer with `cd` (as returned by
+    /// [Coin::check_structure]), where `h_pow` computes `h^e (mod p)`. The coins in
+    /// [CoinFormat::V1] may carry either the current challenge or the legacy one, see
+    /// [legacy_coin_challenge_d].
+    ///
+    /// The challenge carried by the coin is compared unreduced, so that only one encoding of
+    /// a coin is valid. Otherwise the same coin re-encoded with `cd + q` would be accepted
+    /// under a different fingerprint, and spent twice without being detected.
+    fn check_equations(
+        &self,
+        params: &Params,
+        format: CoinFormat,
+        cd: &BigUint,
+        h_pow: impl FnOnce(&BigUint) -> BigUint,
+    ) -> Result<(), VerifyError> {
+        let c2: Vec<&BigUint> = std::iter::once(&self.c2).chain(&self.c2_extra).collect();
+        let ver_cd = coin_challenge_d(
+            params,
+            &self.c1,
+            &c2,
+            &self.c3,
+            &self.c4,
+            &self.c5,
+            self.metadata.as_ref(),
+        );
+
+        let width = params.p.bits().div_ceil(8) as usize;
+        if !bool::from(ct_eq_biguint(&self.cd, &ver_cd, width)) {
+            match format {
+                CoinFormat::V1 if self.is_legacy_challenge(params) => {}
+                CoinFormat::V2 if self.is_legacy_challenge(params) => {
+                    return Err(VerifyError::LegacyFormat)
+                }
+                _ => return Err(VerifyError::ChallengeMismatch),
+            }
         }
 
         // c4 * h^cd = g^c6
-        let lhs = (&self.c4 * h.modpow(&self.cd, &params.p)) % &params.p;
+        let lhs = (&self.c4 * h_pow(cd)) % &params.p;
         let rhs = params.g.modpow(&self.c6, &params.p);
         if lhs != rhs {
-            // println!("Coin::verify fail (1)");
-            return false;
+            return Err(VerifyError::SignatureEqnFailed);
         }
 
         // c5 * c3^cd = c1^c6
-        let lhs = (&self.c5 * self.c3.modpow(&self.cd, &params.p)) % &params.p;
+        let lhs = (&self.c5 * self.c3.modpow(cd, &params.p)) % &params.p;
         let rhs = self.c1.modpow(&self.c6, &params.p);
         if lhs != rhs {
-            // println!("Coin::verify fail (2)");
+            return Err(VerifyError::SignatureEqnFailed);
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the coin carries the legacy challenge, see [legacy_coin_challenge_d].
+    fn is_legacy_challenge(&self, params: &Params) -> bool {
+        if !self.c2_extra.is_empty() || self.metadata.is_some() {
             return false;
         }
+        let ver_cd =
+            legacy_coin_challenge_d(params, &self.c1, &self.c2, &self.c3, &self.c4, &self.c5);
+        let width = params.p.bits().div_ceil(8) as usize;
+        ct_eq_biguint(&self.cd, &ver_cd, width).into()
+    }
+}
+
+/// Computes the challenge `cd` of the coins created before the coin challenge was hashed into
+/// a scalar under the derived HMac key, i.e. `cd = HMac(scheme_key, c1 || c2 || c3 || c4 || c5)
+/// mod p` with the integers in little-endian. The legacy coins carry neither metadata nor
+/// additional show slots.
+fn legacy_coin_challenge_d(
+    params: &Params,
+    c1: &BigUint,
+    c2: &BigUint,
+    c3: &BigUint,
+    c4: &BigUint,
+    c5: &BigUint,
+) -> BigUint {
+    hash_to_number(
+        params.scheme_key.as_bytes(),
+        &[c1, c2, c3, c4, c5].map(|x| x.to_bytes_le()),
+    ) % &params.p
+}
+
+/// Checks that the issuer's identity (h) is an element of the subgroup of order `q` other than 1.
+pub(crate) fn check_issuer_key(h: &Identity, params: &Params) -> Result<(), VerifyError> {
+    // A malformed h (e.g. of small order) makes the signature of the issuer forgeable.
+    if *h == BigUint::from(1u64) || !params.is_subgroup_element(h) {
+        return Err(VerifyError::InvalidIssuerKey);
+    }
+    Ok(())
+}
+
+impl ConstantTimeEq for Coin {
+    /// Compares the coins in constant time with respect to their content, which agrees with
+    /// the derived [PartialEq]. Only the lengths of the encoded coins may be leaked.
+    fn ct_eq(&self, other: &Self) -> Choice {
+        ct_eq_bytes(&self.to_bytes(), &other.to_bytes())
+    }
+}
+
+impl fmt::Display for Coin {
+    /// Formats the coin as a single-line token which can be shared between systems. The token
+    /// consists of a version prefix and the base64url encoding of the coin bytes followed by a
+    /// checksum. The token can be parsed by [Coin::from_str].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = self.to_bytes();
+        let checksum = Sha256::digest(&bytes);
+        bytes.extend_from_slice(&checksum[..COIN_TOKEN_CHECKSUM_LEN]);
+        write!(f, "{}{}", COIN_TOKEN_PREFIX, URL_SAFE_NO_PAD.encode(bytes))
+    }
+}
+
+impl FromStr for Coin {
+    type Err = Error;
 
-        true
+    /// Parses the token formatted by the [Display](std::fmt::Display) implementation of [Coin].
+    /// Returns [Error::UnsupportedVersion] if the version prefix is not recognized, or
+    /// [Error::InvalidChecksum] if the checksum does not match the coin bytes.
+    ///
+    /// The coin is not verified. Call [Coin::verify] before accepting it.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let encoded = s
+            .strip_prefix(COIN_TOKEN_PREFIX)
+            .ok_or(Error::UnsupportedVersion)?;
+        let bytes = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|_| Error::DeserializationFailed)?;
+        if bytes.len() < COIN_TOKEN_CHECKSUM_LEN {
+            return Err(Error::DeserializationFailed);
+        }
+        let (bytes, checksum) = bytes.split_at(bytes.len() - COIN_TOKEN_CHECKSUM_LEN);
+        if Sha256::digest(bytes)[..COIN_TOKEN_CHECKSUM_LEN] != *checksum {
+            return Err(Error::InvalidChecksum);
+        }
+        Self::from_bytes(bytes)
     }
 }
 
 /// A mathematic representation of a "coin" which being spent. As compared to
 /// the struct [Coin], it includes additional parameters which are created by
 /// the spender upon a coin challenge during coin spending process.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpentCoin {
     /// The coin sent by the spender.
     pub coin: Coin,
     pub(crate) r1: BigUint,
     pub(crate) r2: BigUint,
+    /// The show slot of the coin used in spending.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) slot: u32,
 }
 
 impl PartialEq for SpentCoin {
@@ -118,38 +1024,279 @@ impl PartialEq for SpentCoin {
 }
 impl Eq for SpentCoin {}
 
+impl ConstantTimeEq for SpentCoin {
+    /// Compares the spent coins in constant time, which agrees with the [PartialEq] of
+    /// [SpentCoin], i.e. only the [Coin] information is compared.
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.coin.ct_eq(&other.coin)
+    }
+}
+
+impl From<&SpentCoin> for Coin {
+    /// Takes a copy of the coin being spent, e.g. to verify it independently.
+    fn from(spent_coin: &SpentCoin) -> Self {
+        spent_coin.coin.clone()
+    }
+}
+
 impl SpentCoin {
+    /// Builds a spent coin from a coin and the responses `r1` and `r2` created by the spender
+    /// upon a coin challenge, e.g. when the spent coin is received from other implementations.
+    ///
+    /// The spent coin is not verified. Call [SpentCoin::verify] with the coin challenge before
+    /// accepting it.
+    pub fn from_parts(coin: Coin, r1: BigUint, r2: BigUint) -> Self {
+        Self {
+            coin,
+            r1,
+            r2,
+            slot: 0,
+        }
+    }
+
+    /// Encodes the spent coin into bytes, i.e. the length-prefixed encoding of the coin,
+    /// followed by the responses and the show slot. The spent coin can be decoded by
+    /// [SpentCoin::from_bytes].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with_endianness(Endianness::Little)
+    }
+
+    /// Encodes the spent coin as [SpentCoin::to_bytes] does, with the coin encoded by
+    /// [Coin::to_bytes_with_endianness], in the byte order `endianness`. The spent coin can be
+    /// decoded by [SpentCoin::from_bytes_with_endianness] with the same byte order.
+    pub fn to_bytes_with_endianness(&self, endianness: Endianness) -> Vec<u8> {
+        self.encode(
+            self.coin.to_bytes_with_endianness(endianness),
+            0,
+            endianness,
+        )
+    }
+
+    /// Encodes the spent coin into bytes as [SpentCoin::to_bytes] does, with the coin encoded by
+    /// [Coin::to_padded_bytes] and the responses padded to [Params::serialized_scalar_len]. The
+    /// spent coin can be decoded by [SpentCoin::from_bytes].
+    pub fn to_padded_bytes(&self, params: &Params) -> Vec<u8> {
+        self.encode(
+            self.coin.to_padded_bytes(params),
+            params.serialized_scalar_len(),
+            Endianness::Little,
+        )
+    }
+
+    /// Encodes the spent coin with the encoded coin and the responses padded to the length, in
+    /// the byte order `endianness`.
+    fn encode(&self, coin_bytes: Vec<u8>, scalar_len: usize, endianness: Endianness) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_bytes_in(&mut buf, &coin_bytes, endianness);
+        write_biguint_in(&mut buf, &self.r1, scalar_len, endianness);
+        write_biguint_in(&mut buf, &self.r2, scalar_len, endianness);
+        buf.extend_from_slice(&endianness.u32_to_bytes(self.slot));
+        buf
+    }
+
+    /// Decodes a spent coin from the bytes encoded by [SpentCoin::to_bytes]. Returns
+    /// [Error::DeserializationFailed] if the bytes are malformed, or a field is declared longer
+    /// than 4096 bytes.
+    ///
+    /// The spent coin is not verified. Call [SpentCoin::verify] with the coin challenge before
+    /// accepting it.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes_with_endianness(bytes, Endianness::Little)
+    }
+
+    /// Decodes a spent coin from the bytes encoded by [SpentCoin::to_bytes_with_endianness] in
+    /// the byte order `endianness`, as [SpentCoin::from_bytes] does.
+    pub fn from_bytes_with_endianness(bytes: &[u8], endianness: Endianness) -> Result<Self, Error> {
+        let mut reader = Reader::with_endianness(bytes, endianness);
+        let spent_coin = Self {
+            coin: Coin::from_bytes_with_endianness(reader.read_bytes()?, endianness)?,
+            r1: reader.read_biguint()?,
+            r2: reader.read_biguint()?,
+            slot: reader.read_u32()?,
+        };
+        reader.finish()?;
+        Ok(spent_coin)
+    }
+
+    /// Returns the responses `(r1, r2)` created by the spender upon the coin challenge, e.g. for
+    /// handing them to a verifier implemented in another language. The spent coin can be
+    /// rebuilt by [SpentCoin::from_parts].
+    pub fn responses(&self) -> (&BigUint, &BigUint) {
+        (&self.r1, &self.r2)
+    }
+
+    /// Returns the show slot of the coin used in spending, which is always 0 for a coin that
+    /// can be shown once.
+    pub fn slot(&self) -> u32 {
+        self.slot
+    }
+
     // Returns true if the spent coin is valid upon the coin challenge.
     pub fn verify(&self, challenge: &CoinChallenge, params: &Params) -> bool {
+        let Some(c2) = self.coin.slot_commitment(self.slot) else {
+            return false;
+        };
+        // The responses decoded from crafted bytes may not be reduced mod q.
+        let r1 = &self.r1 % &params.q;
+        let r2 = &self.r2 % &params.q;
         // c1^d * c2 == g1^r1 * g2^r2
-        let lhs = (self.coin.c1.modpow(&challenge.0, &params.p) * &self.coin.c2) % &params.p;
-        let rhs = (params.g1.modpow(&self.r1, &params.p) * params.g2.modpow(&self.r2, &params.p))
-            % &params.p;
+        let lhs = (self.coin.c1.modpow(&challenge.0, &params.p) * c2) % &params.p;
+        let rhs = (params.g1.modpow(&r1, &params.p) * params.g2.modpow(&r2, &params.p)) % &params.p;
         lhs == rhs
     }
 
+    /// Verifies that the underlying coin is issued by the issuer whose identity is `h`, as
+    /// [Coin::verify] does, and then that the spent coin is valid upon the coin challenge, as
+    /// [SpentCoin::verify] does. Returns the reason of the first failure, which is
+    /// [VerifyError::ResponseEqnFailed] if only the responses are invalid.
+    pub fn verify_full(
+        &self,
+        h: &Identity,
+        challenge: &CoinChallenge,
+        params: &Params,
+    ) -> Result<(), VerifyError> {
+        self.coin.check(h, params)?;
+        if !self.verify(challenge, params) {
+            return Err(VerifyError::ResponseEqnFailed);
+        }
+        Ok(())
+    }
+
+    /// Verifies the spent coins upon their challenges as [SpentCoin::verify] does, e.g. for
+    /// auditing the shows of a k-show coin together. Returns true for each valid spent coin, in
+    /// the order of the entries.
+    ///
+    /// The entries spending the same show slot of the same coin with different responses (i.e.
+    /// the slot is double spent) are flagged as false, even if they are valid individually.
+    /// The identical entries are not flagged.
+    pub fn verify_many(spent: &[(SpentCoin, CoinChallenge)], params: &Params) -> Vec<bool> {
+        let mut responses = HashMap::new();
+        let mut double_spent = HashSet::new();
+        let keys: Vec<(CoinFingerprint, u32)> = spent
+            .iter()
+            .map(|(spent_coin, _)| {
+                let key = (spent_coin.coin.fingerprint(), spent_coin.slot);
+                let (r1, r2) = responses
+                    .entry(key)
+                    .or_insert((&spent_coin.r1, &spent_coin.r2));
+                if (*r1, *r2) != (&spent_coin.r1, &spent_coin.r2) {
+                    double_spent.insert(key);
+                }
+                key
+            })
+            .collect();
+
+        spent
+            .iter()
+            .zip(keys)
+            .map(|((spent_coin, challenge), key)| {
+                !double_spent.contains(&key) && spent_coin.verify(challenge, params)
+            })
+            .collect()
+    }
+
+    /// Returns true if the spent coin is valid upon the challenge created by
+    /// [CoinChallenge::new_bound] from the message and the identity claimed by the spender.
+    pub fn verify_bound(
+        &self,
+        message: &[u8],
+        spender_identity: &Identity,
+        params: &Params,
+    ) -> bool {
+        let challenge = CoinChallenge::new_bound(message, &self.coin, spender_identity);
+        self.verify(&challenge, params)
+    }
+
+    /// Assembles a transcript of spending this coin upon the coin challenge, which records
+    /// the outcome of the verification. The transcript can be handed to a third party for
+    /// adjudicating a double spending claim.
+    pub fn transcript(&self, challenge: &CoinChallenge, params: &Params) -> SpendTranscript {
+        SpendTranscript {
+            spent_coin: self.clone(),
+            challenge: challenge.clone(),
+            params: params.clone(),
+            verified: self.verify(challenge, params),
+        }
+    }
+
     /// Given a double spent coin, compute the identity of the double spender.
     ///
-    /// ## Panics
-    /// Panics if the double_spent_coin is not referring to the same coin. It is caller responsibility
-    /// to make sure the input `double_spent_coin` is actually "double spent".
-    pub fn reveal_identity(&self, double_spent_coin: &SpentCoin, params: &Params) -> Identity {
+    /// Returns [Error::DifferentCoins] if the double_spent_coin is not referring to the same
+    /// coin, [Error::DifferentSlots] if a k-show coin was spent on different show slots, or
+    /// [Error::SameChallenge] if the responses of the two spent coins cannot be used to recover
+    /// the identity because both coins were spent upon the same challenge. It is caller
+    /// responsibility to make sure the input `double_spent_coin` is actually "double spent".
+    pub fn reveal_identity(
+        &self,
+        double_spent_coin: &SpentCoin,
+        params: &Params,
+    ) -> Result<Identity, Error> {
         let coin_1 = self;
         let coin_2 = double_spent_coin;
-        assert!(coin_1 == coin_2);
+        if coin_1 != coin_2 {
+            return Err(Error::DifferentCoins);
+        }
+        if coin_1.slot != coin_2.slot {
+            return Err(Error::DifferentSlots);
+        }
 
         // g1 ^ ( (r1-r1') / (r2-r2') )
-        let r1_diff = if coin_1.r1 > coin_2.r1 {
-            &coin_1.r1 - &coin_2.r1
-        } else {
-            (&coin_1.r1 + &params.q - &coin_2.r1) % &params.q
-        };
-        let r2_diff = if coin_1.r2 > coin_2.r2 {
-            &coin_1.r2 - &coin_2.r2
-        } else {
-            (&coin_1.r2 + &params.q - &coin_2.r2) % &params.q
-        };
-        let exponent = (r1_diff * r2_diff.modinv(&params.q).unwrap()) % &params.q;
-        params.g1.modpow(&exponent, &params.p)
+        let (r1_diff, r2_diff) = coin_1.response_diffs(coin_2, &params.q);
+        if r2_diff == BigUint::ZERO {
+            return Err(Error::SameChallenge);
+        }
+        let exponent = (r1_diff * modinv_checked(&r2_diff, &params.q)?) % &params.q;
+        telemetry::count(telemetry::DOUBLE_SPEND_DETECTED, 1);
+        Ok(params.g1_pow(&exponent))
+    }
+
+    /// Computes the identity of the double spender as [SpentCoin::reveal_identity] does, after
+    /// verifying both spent coins upon their own challenges, so that an unverified spent coin
+    /// cannot yield a bogus identity.
+    ///
+    /// Returns [Error::InvalidSpentCoin] if either spent coin is not valid upon its challenge, or
+    /// [Error::SameChallenge] if both coins were spent upon the same challenge, in addition to
+    /// the errors of [SpentCoin::reveal_identity].
+    pub fn reveal_identity_verified(
+        &self,
+        double_spent_coin: &SpentCoin,
+        challenge: &CoinChallenge,
+        double_spent_challenge: &CoinChallenge,
+        params: &Params,
+    ) -> Result<Identity, Error> {
+        if !self.verify(challenge, params)
+            || !double_spent_coin.verify(double_spent_challenge, params)
+        {
+            return Err(Error::InvalidSpentCoin);
+        }
+        if challenge == double_spent_challenge {
+            return Err(Error::SameChallenge);
+        }
+        self.reveal_identity(double_spent_coin, params)
+    }
+
+    /// Compares the responses of the spent coins in constant time.
+    pub(crate) fn ct_eq_responses(&self, other: &SpentCoin) -> Choice {
+        ct_eq_biguint(&self.r1, &other.r1, 0) & ct_eq_biguint(&self.r2, &other.r2, 0)
+    }
+
+    /// Returns the differences of the responses `(r1-r1', r2-r2')` (mod q) of the two spent
+    /// coins. The responses are reduced mod q before subtraction, which is in constant time by
+    /// [sub_mod].
+    pub(crate) fn response_diffs(&self, other: &SpentCoin, q: &BigUint) -> (BigUint, BigUint) {
+        (
+            sub_mod(&self.r1, &other.r1, q),
+            sub_mod(&self.r2, &other.r2, q),
+        )
+    }
+
+    /// Reduces the responses and the challenge `cd` of the coin mod q, which are equivalent as
+    /// the exponents of the elements of the subgroup of order q. A spent coin decoded from
+    /// crafted bytes may carry the responses `r >= q`, and a coin re-encoded with `cd + q` is
+    /// the same coin under a different fingerprint.
+    pub(crate) fn normalize(&mut self, q: &BigUint) {
+        self.r1 %= q;
+        self.r2 %= q;
+        self.coin.cd %= q;
     }
 }
@@ -1,31 +1,440 @@
 //! Defines cryptograhic functions used for the library.
 
+use std::io::{self, Read};
+
+use hkdf::Hkdf;
 use hmac::{Hmac, Mac};
 use num_bigint::{BigUint, RandBigInt};
-use sha2::Sha256;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::{
+    math::{is_subgroup_generator, modinv_checked},
+    Error,
+};
+
+/// Returns a random number (mod m) sampled from the random number generator.
+pub(crate) fn random_number_from<R: RngCore + ?Sized>(rng: &mut R, m: &BigUint) -> BigUint {
+    random_range(&BigUint::ZERO, m, rng)
+}
+
+/// Returns a random number of at most `bits` bits, i.e. in the range `[0, 2^bits)`.
+pub(crate) fn random_bits<R: RngCore + ?Sized>(bits: u64, rng: &mut R) -> BigUint {
+    rng.gen_biguint(bits)
+}
+
+/// Returns a random number in the range `[low, high)`.
+///
+/// ## Panics
+/// Panics if the range is empty, i.e. `low >= high`.
+pub(crate) fn random_range<R: RngCore + ?Sized>(
+    low: &BigUint,
+    high: &BigUint,
+    rng: &mut R,
+) -> BigUint {
+    rng.gen_biguint_range(low, high)
+}
 
-/// Returns a random number (mod m).
-pub(crate) fn random_number(m: &BigUint) -> BigUint {
-    // TODO : allow flexible random function
-    let mut rng = rand::thread_rng();
-    rng.gen_biguint_range(&BigUint::ZERO, m)
+/// Returns a random non-zero number (mod m), i.e. in the range `[1, m)`, e.g. for the secret
+/// values which must be invertible (mod m).
+///
+/// ## Panics
+/// Panics if `m <= 1`.
+pub(crate) fn random_nonzero<R: RngCore + ?Sized>(m: &BigUint, rng: &mut R) -> BigUint {
+    random_range(&BigUint::from(1u64), m, rng)
+}
+
+/// Returns the multiplicative inverses of all of the `values` (mod `modulus`) by using
+/// Montgomery's trick, which takes only one modular inversion for the whole batch. Returns
+/// [Error::NonInvertible] if any of the values is not invertible.
+pub(crate) fn batch_inv_mod(values: &[BigUint], modulus: &BigUint) -> Result<Vec<BigUint>, Error> {
+    // prefix[i] = values[0] * .. * values[i-1]
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut acc = BigUint::from(1u64);
+    for value in values {
+        prefix.push(acc.clone());
+        acc = (acc * value) % modulus;
+    }
+
+    // acc = (values[0] * .. * values[i])^-1, walking backwards
+    let mut acc = modinv_checked(&acc, modulus)?;
+    let mut inverses = vec![BigUint::ZERO; values.len()];
+    for (i, value) in values.iter().enumerate().rev() {
+        inverses[i] = (&acc * &prefix[i]) % modulus;
+        acc = (acc * value) % modulus;
+    }
+    Ok(inverses)
+}
+
+/// Number of bits of the exponent handled by each window of a [FixedBaseTable].
+const WINDOW_BITS: u64 = 4;
+
+/// A table of the powers of a fixed base `x`, i.e. `x^(j * 2^(WINDOW_BITS * i))` for each window
+/// `i` of the exponent and each digit `j` of a window, which computes `x^e` by one modular
+/// multiplication per window without squaring.
+pub(crate) struct FixedBaseTable {
+    p: BigUint,
+    windows: Vec<Vec<BigUint>>,
+}
+
+impl FixedBaseTable {
+    /// Precomputes the powers of `x` for the exponents of at most `bits` bits.
+    pub(crate) fn new(x: &BigUint, bits: u64, p: &BigUint) -> Self {
+        let num_windows = bits.div_ceil(WINDOW_BITS);
+        let mut windows = Vec::with_capacity(num_windows as usize);
+        let mut base = x.clone();
+        for _ in 0..num_windows {
+            let mut window = Vec::with_capacity(1 << WINDOW_BITS);
+            let mut acc = BigUint::from(1u64);
+            for _ in 0..(1 << WINDOW_BITS) {
+                window.push(acc.clone());
+                acc = (acc * &base) % p;
+            }
+            // base^(2^WINDOW_BITS) is the base of the next window.
+            base = acc;
+            windows.push(window);
+        }
+        Self {
+            p: p.clone(),
+            windows,
+        }
+    }
+
+    /// Returns `x^e (mod p)`.
+    pub(crate) fn pow(&self, e: &BigUint) -> BigUint {
+        let digits = e.to_radix_le(1 << WINDOW_BITS);
+        if digits.len() > self.windows.len() {
+            // The exponent exceeds the table, e.g. not reduced mod q.
+            return self.windows[0][1].modpow(e, &self.p);
+        }
+        digits
+            .iter()
+            .zip(&self.windows)
+            .filter(|(digit, _)| **digit != 0)
+            .fold(BigUint::from(1u64), |acc, (digit, window)| {
+                (acc * &window[*digit as usize]) % &self.p
+            })
+    }
+}
+
+/// Creates a Schnorr signature `(e, s)` by the secret key `x` in the group generated by `g`,
+/// where `e = challenge(g^k)` for a random nonce `k` sampled from `rng`, and
+/// `s = k + e*x (mod q)`.
+pub(crate) fn schnorr_sign<R: RngCore + ?Sized>(
+    x: &BigUint,
+    g: &BigUint,
+    p: &BigUint,
+    q: &BigUint,
+    rng: &mut R,
+    challenge: impl FnOnce(&BigUint) -> BigUint,
+) -> (BigUint, BigUint) {
+    let k = random_nonzero(q, rng);
+    let e = challenge(&g.modpow(&k, p));
+    let s = (k + &e * x) % q;
+    (e, s)
+}
+
+/// Verifies the Schnorr signature `(e, s)` created by [schnorr_sign] under the public key
+/// `y = g^x`, i.e. checks that `e = challenge(g^s * y^-e)`. The public key is assumed to be
+/// an element of the subgroup of order `q`.
+pub(crate) fn schnorr_verify(
+    e: &BigUint,
+    s: &BigUint,
+    y: &BigUint,
+    g: &BigUint,
+    p: &BigUint,
+    q: &BigUint,
+    challenge: impl FnOnce(&BigUint) -> BigUint,
+) -> bool {
+    if e >= q || s >= q {
+        return false;
+    }
+    // y^-e = y^(q-e) as y^q = 1
+    let r = (g.modpow(s, p) * y.modpow(&(q - e), p)) % p;
+    challenge(&r) == *e
+}
+
+/// Compares the big integers in constant time over their little-endian encodings padded to
+/// `width` bytes. The encodings longer than `width` are padded to the longer length, so that
+/// only the length of the longer value may be leaked.
+pub(crate) fn ct_eq_biguint(a: &BigUint, b: &BigUint, width: usize) -> Choice {
+    let a = a.to_bytes_le();
+    let b = b.to_bytes_le();
+    let len = width.max(a.len()).max(b.len());
+    pad_to(a, len).ct_eq(&pad_to(b, len))
+}
+
+/// Compares the byte strings in constant time after padding them to the same length. The
+/// lengths of the byte strings may be leaked.
+pub(crate) fn ct_eq_bytes(a: &[u8], b: &[u8]) -> Choice {
+    let len = a.len().max(b.len());
+    (a.len() as u64).ct_eq(&(b.len() as u64))
+        & pad_to(a.to_vec(), len).ct_eq(&pad_to(b.to_vec(), len))
+}
+
+fn pad_to(mut bytes: Vec<u8>, len: usize) -> Vec<u8> {
+    bytes.resize(len, 0);
+    bytes
+}
+
+/// Domain tag for deriving the HMac key from the scheme key.
+const SCHEME_KEY_TAG: &[u8] = b"brands-rs/scheme-key";
+
+/// Domain tag for deriving the HMac key from the scheme key and a tenant id.
+const TENANT_SCHEME_KEY_TAG: &[u8] = b"brands-rs/scheme-key/tenant";
+
+/// Derives a fixed-length HMac key from the scheme key, so that the keys of any length are
+/// separated by the same means, instead of the long keys being hashed implicitly by HMac.
+///
+/// A non-empty tenant id is folded into the key, so that the hashing domains of the tenants
+/// sharing the same scheme key are isolated. The key derived with an empty tenant id is the
+/// same as the one derived without tenant.
+pub(crate) fn derive_hmac_key(scheme_key: &[u8], tenant_id: &[u8]) -> [u8; 32] {
+    if tenant_id.is_empty() {
+        return Sha256::new()
+            .chain_update(SCHEME_KEY_TAG)
+            .chain_update(scheme_key)
+            .finalize()
+            .into();
+    }
+    Sha256::new()
+        .chain_update(TENANT_SCHEME_KEY_TAG)
+        .chain_update((scheme_key.len() as u64).to_le_bytes())
+        .chain_update(scheme_key)
+        .chain_update(tenant_id)
+        .finalize()
+        .into()
 }
 
 /// Converts a key-data pair into a number by using HMac-Sha256 over the content which is concatenation of
 /// key and data.
 pub(crate) fn hash_to_number<B: AsRef<[u8]>, T: AsRef<[B]>>(key: &[u8], data: &T) -> BigUint {
-    // TODO : allow flexible hashing algorithm
-    let strings_as_bytes: Vec<u8> = data
+    // TODO : allow flexible hashing algorithm. The coin challenges are hashed by this function,
+    // and the coins by hash_to_scalar, so both must switch to the same algorithm together, or
+    // a coin could be challenged under a different hash than it was withdrawn under.
+    mac_to_number(Hmac::<Sha256>::new_from_slice(key).unwrap(), data)
+}
+
+/// Converts the key-data pairs of the same key into numbers as [hash_to_number] does, with the
+/// keyed HMac state set up once and cloned for each data.
+pub(crate) fn hash_to_numbers<B: AsRef<[u8]>, T: AsRef<[B]>>(
+    key: &[u8],
+    data: impl IntoIterator<Item = T>,
+) -> Vec<BigUint> {
+    let keyed = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    data.into_iter()
+        .map(|data| mac_to_number(keyed.clone(), &data))
+        .collect()
+}
+
+fn mac_to_number<B: AsRef<[u8]>, T: AsRef<[B]>>(mac: Hmac<Sha256>, data: &T) -> BigUint {
+    let hash_bytes = data
         .as_ref()
         .iter()
-        .flat_map(|s| s.as_ref().to_vec())
-        .collect();
-
-    let hash_bytes = Hmac::<Sha256>::new_from_slice(key)
-        .unwrap()
-        .chain_update(strings_as_bytes)
+        .fold(mac, |mac, s| mac.chain_update(s))
         .finalize()
-        .into_bytes()
-        .to_vec();
+        .into_bytes();
     BigUint::from_bytes_le(&hash_bytes)
 }
+
+/// The block size of Sha256, beyond which the HMac key is hashed.
+const SHA256_BLOCK_SIZE: usize = 64;
+
+/// Reads an HMac-Sha256 key from the stream, and returns the key that is equivalent for HMac,
+/// i.e. the key itself if it fits in a block of Sha256, or the Sha256 hash of the key otherwise.
+/// The key is hashed as it is read, so that a long key is not buffered in memory.
+pub(crate) fn read_hmac_key(mut reader: impl Read) -> io::Result<Vec<u8>> {
+    let mut hasher = Sha256::new();
+    let mut head = Vec::with_capacity(SHA256_BLOCK_SIZE + 1);
+    let mut len = 0;
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buf[..n]);
+        if len <= SHA256_BLOCK_SIZE {
+            let take = n.min(SHA256_BLOCK_SIZE + 1 - len);
+            head.extend_from_slice(&buf[..take]);
+        }
+        len += n;
+    }
+    if len > SHA256_BLOCK_SIZE {
+        Ok(hasher.finalize().to_vec())
+    } else {
+        Ok(head)
+    }
+}
+
+/// Number of extra bits of the hash output for reducing it into a scalar, which makes the bias
+/// of the reduction negligible.
+const WIDE_REDUCTION_EXTRA_BITS: u64 = 128;
+
+/// Converts a key-data pair into a scalar uniformly distributed in the range `[0, q)`. The
+/// HMac-Sha256 outputs over a block counter and the data are concatenated into a number which
+/// is 128 bits wider than `q`, and then reduced modulo `q` (i.e. wide reduction).
+pub(crate) fn hash_to_scalar<B: AsRef<[u8]>, T: AsRef<[B]>>(
+    key: &[u8],
+    data: &T,
+    q: &BigUint,
+) -> BigUint {
+    let len = (q.bits() + WIDE_REDUCTION_EXTRA_BITS).div_ceil(8) as usize;
+    let mut hash_bytes = Vec::with_capacity(len);
+    let mut counter = 0u32;
+    while hash_bytes.len() < len {
+        let mac = Hmac::<Sha256>::new_from_slice(key)
+            .unwrap()
+            .chain_update(counter.to_le_bytes());
+        let block = data
+            .as_ref()
+            .iter()
+            .fold(mac, |mac, s| mac.chain_update(s))
+            .finalize()
+            .into_bytes();
+        hash_bytes.extend_from_slice(&block);
+        counter += 1;
+    }
+    hash_bytes.truncate(len);
+    BigUint::from_bytes_le(&hash_bytes) % q
+}
+
+/// Converts a key-data pair into a generator of the subgroup of quadratic residues modulo the
+/// safe prime `p` (i.e. of order `q = (p - 1) / 2`), by squaring a number derived as
+/// [hash_to_scalar] does. The data is hashed along with a counter, which is increased until the
+/// element generates the subgroup (see [is_subgroup_generator]).
+pub(crate) fn hash_to_subgroup(key: &[u8], data: &[&[u8]], p: &BigUint) -> BigUint {
+    let q = (p - 1u64) >> 1;
+    (0u32..)
+        .map(|counter| {
+            let counter = counter.to_le_bytes();
+            let data: Vec<&[u8]> = data.iter().copied().chain([counter.as_slice()]).collect();
+            hash_to_scalar(key, &data, p).modpow(&BigUint::from(2u64), p)
+        })
+        .find(|x| is_subgroup_generator(x, &q, p))
+        .unwrap()
+}
+
+/// Derives a number in the range `[1, q)` from the HKDF pseudorandom key by expanding it over
+/// the `info` into a number which is 128 bits wider than `q - 1`, as [hash_to_scalar] does.
+pub(crate) fn hkdf_to_scalar(hkdf: &Hkdf<Sha256>, info: &[&[u8]], q: &BigUint) -> BigUint {
+    let len = (q.bits() + WIDE_REDUCTION_EXTRA_BITS).div_ceil(8) as usize;
+    let mut okm = vec![0u8; len];
+    // The length is far below the limit (255 blocks) for the groups in use.
+    hkdf.expand_multi_info(info, &mut okm).unwrap();
+    BigUint::from_bytes_le(&okm) % (q - 1u64) + 1u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_ct_eq_biguint() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let bits = rng.gen_range(0..=256);
+            let a = random_bits(bits, &mut rng);
+            // near-equal values
+            let b = match rng.gen_range(0..4) {
+                0 => a.clone(),
+                1 => &a + 1u64,
+                2 => &a ^ (BigUint::from(1u64) << rng.gen_range(0..256)),
+                _ => rng.gen_biguint(bits),
+            };
+            for width in [0, 16, 32, 64] {
+                assert_eq!(bool::from(ct_eq_biguint(&a, &b, width)), a == b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_to_scalar() {
+        let q = BigUint::from(13u64);
+        let mut counts = [0u32; 13];
+        for i in 0..13000u32 {
+            let x = hash_to_scalar(b"key", &[i.to_le_bytes()], &q);
+            assert!(x < q);
+            counts[x.to_u32_digits().first().copied().unwrap_or(0) as usize] += 1;
+        }
+        // Each of the 13 values is expected 1000 times.
+        assert!(counts.iter().all(|&c| (800..1200).contains(&c)));
+
+        let q = BigUint::from(1u64) << 1535;
+        for i in 0..100u32 {
+            assert!(hash_to_scalar(b"key", &[i.to_le_bytes()], &q) < q);
+        }
+    }
+
+    #[test]
+    fn test_random_bits() {
+        let mut rng = rand::thread_rng();
+        assert_eq!(random_bits(0, &mut rng), BigUint::ZERO);
+        for _ in 0..100 {
+            assert!(random_bits(1, &mut rng) <= BigUint::from(1u64));
+            assert!(random_bits(130, &mut rng).bits() <= 130);
+        }
+    }
+
+    #[test]
+    fn test_random_range() {
+        let mut rng = rand::thread_rng();
+        let low = BigUint::from(5u64);
+        assert_eq!(random_range(&low, &BigUint::from(6u64), &mut rng), low);
+        let high = BigUint::from(8u64);
+        for _ in 0..100 {
+            let x = random_range(&low, &high, &mut rng);
+            assert!(low <= x && x < high);
+        }
+        let result = std::panic::catch_unwind(|| {
+            random_range(&low, &low, &mut rand::thread_rng());
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_random_nonzero() {
+        let mut rng = rand::thread_rng();
+        let one = BigUint::from(1u64);
+        assert_eq!(random_nonzero(&BigUint::from(2u64), &mut rng), one);
+        let m = BigUint::from(3u64);
+        for _ in 0..100 {
+            let x = random_nonzero(&m, &mut rng);
+            assert!(one <= x && x < m);
+        }
+    }
+
+    #[test]
+    fn test_read_hmac_key() {
+        for len in [0, 1, 63, 64, 65, 4096, 10_000] {
+            let key: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let read_key = read_hmac_key(key.as_slice()).unwrap();
+            assert_eq!(
+                hash_to_number(&read_key, &[b"data"]),
+                hash_to_number(&key, &[b"data"])
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_to_number_incremental() {
+        let key = b"brandskey";
+        let data = [vec![1u8; 1000], vec![], vec![2u8; 3], vec![3u8; 100_000]];
+
+        let concatenated: Vec<u8> = data.iter().flatten().copied().collect();
+        let expected = Hmac::<Sha256>::new_from_slice(key)
+            .unwrap()
+            .chain_update(concatenated)
+            .finalize()
+            .into_bytes();
+
+        assert_eq!(
+            hash_to_number(key, &data),
+            BigUint::from_bytes_le(&expected)
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Defines the denominations of coins tagged by their units (e.g. currencies) at the type level,
+//! so that the coins of different units cannot be mixed up.
+
+use std::{fmt, marker::PhantomData};
+
+use crate::{
+    coin::{CoinChallenge, CoinMetadata, SpentCoin},
+    ledger::{DepositLedger, DepositOutcome},
+    params::Params,
+    Error,
+};
+
+/// A unit of the value of coins (e.g. a currency), which is usually implemented by a type
+/// without values.
+///
+/// ### Example
+/// ```
+/// enum Eur {}
+///
+/// impl brands::Unit for Eur {
+///     const CODE: &'static [u8] = b"EUR";
+/// }
+/// ```
+pub trait Unit {
+    /// The code which identifies the unit. It is bound into the coins as the attributes of the
+    /// [CoinMetadata], so it should be distinct among the units.
+    const CODE: &'static [u8];
+}
+
+/// A denomination (i.e. the amount) of a coin in the unit `U`, which is bound into the coin by
+/// converting it into the [CoinMetadata] for
+/// [withdraw_with_metadata](crate::Spender::withdraw_with_metadata).
+pub struct Denomination<U: Unit> {
+    amount: u64,
+    unit: PhantomData<U>,
+}
+
+impl<U: Unit> Denomination<U> {
+    pub fn new(amount: u64) -> Self {
+        Self {
+            amount,
+            unit: PhantomData,
+        }
+    }
+
+    /// Returns the amount in the unit `U`.
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+}
+
+impl<U: Unit> Clone for Denomination<U> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<U: Unit> Copy for Denomination<U> {}
+
+impl<U: Unit> PartialEq for Denomination<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.amount == other.amount
+    }
+}
+
+impl<U: Unit> Eq for Denomination<U> {}
+
+impl<U: Unit> fmt::Debug for Denomination<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, String::from_utf8_lossy(U::CODE))
+    }
+}
+
+impl<U: Unit> From<Denomination<U>> for CoinMetadata {
+    fn from(denomination: Denomination<U>) -> Self {
+        CoinMetadata::new(denomination.amount, U::CODE.to_vec())
+    }
+}
+
+/// A [SpentCoin] whose coin is denominated in the unit `U`, which can be deposited only into a
+/// [DenominatedLedger] of the same unit.
+pub struct DenominatedSpentCoin<U: Unit> {
+    spent_coin: SpentCoin,
+    denomination: Denomination<U>,
+}
+
+impl<U: Unit> DenominatedSpentCoin<U> {
+    /// Tags the spent coin with the unit `U`. The spent coin is assumed to be verified, so that
+    /// the metadata bound into the coin is authentic.
+    ///
+    /// Returns [Error::UnitMismatch] if the coin is not denominated in the unit `U`.
+    pub fn new(spent_coin: SpentCoin) -> Result<Self, Error> {
+        let denomination = match &spent_coin.coin.metadata {
+            Some(metadata) if metadata.attributes == U::CODE => {
+                Denomination::new(metadata.denomination)
+            }
+            _ => return Err(Error::UnitMismatch),
+        };
+        Ok(Self {
+            spent_coin,
+            denomination,
+        })
+    }
+
+    /// Returns the denomination of the coin.
+    pub fn denomination(&self) -> Denomination<U> {
+        self.denomination
+    }
+
+    /// Returns the untagged spent coin.
+    pub fn into_inner(self) -> SpentCoin {
+        self.spent_coin
+    }
+}
+
+/// A [DepositLedger] which accepts only the coins denominated in the unit `U`.
+pub struct DenominatedLedger<U: Unit> {
+    ledger: DepositLedger,
+    unit: PhantomData<U>,
+}
+
+impl<U: Unit> DenominatedLedger<U> {
+    pub fn new(params: Params) -> Self {
+        Self {
+            ledger: DepositLedger::new(params),
+            unit: PhantomData,
+        }
+    }
+
+    /// Deposits the spent coin with the coin challenge upon which it was spent, as
+    /// [DepositLedger::deposit] does.
+    pub fn deposit(
+        &mut self,
+        spent_coin: DenominatedSpentCoin<U>,
+        challenge: CoinChallenge,
+    ) -> Result<DepositOutcome, Error> {
+        self.ledger.deposit(spent_coin.spent_coin, challenge)
+    }
+}
@@ -0,0 +1,202 @@
+//! Implements the binary encoding of the structs in the scheme.
+//!
+//! A big integer is encoded as a 4-byte little-endian length prefix followed by its bytes in
+//! little-endian order, unless the big-endian [Endianness] is chosen for the encoding.
+
+use num_bigint::BigUint;
+
+use crate::Error;
+
+/// Maximum length in bytes of an encoded big integer, which is far above the size of the
+/// values in the scheme (e.g. 1024 bytes for a 8192-bit modulus). A longer length prefix is
+/// rejected before the bytes are read.
+pub(crate) const MAX_BIGUINT_LEN: usize = 4096;
+
+/// The byte order of the integers (including the length prefixes) in the binary encodings, and
+/// of the big integers in the hash of a coin. Little-endian is the default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Endianness {
+    /// Least significant byte first.
+    #[default]
+    Little,
+    /// Most significant byte first.
+    Big,
+}
+
+impl Endianness {
+    #[cfg(feature = "serde")]
+    pub(crate) fn is_little(&self) -> bool {
+        *self == Endianness::Little
+    }
+
+    pub(crate) fn biguint_to_bytes(self, x: &BigUint) -> Vec<u8> {
+        match self {
+            Endianness::Little => x.to_bytes_le(),
+            Endianness::Big => x.to_bytes_be(),
+        }
+    }
+
+    pub(crate) fn biguint_from_bytes(self, bytes: &[u8]) -> BigUint {
+        match self {
+            Endianness::Little => BigUint::from_bytes_le(bytes),
+            Endianness::Big => BigUint::from_bytes_be(bytes),
+        }
+    }
+
+    pub(crate) fn u32_to_bytes(self, x: u32) -> [u8; 4] {
+        match self {
+            Endianness::Little => x.to_le_bytes(),
+            Endianness::Big => x.to_be_bytes(),
+        }
+    }
+
+    pub(crate) fn u64_to_bytes(self, x: u64) -> [u8; 8] {
+        match self {
+            Endianness::Little => x.to_le_bytes(),
+            Endianness::Big => x.to_be_bytes(),
+        }
+    }
+
+    /// Pads the bytes of a big integer with zeros at the most significant end to `width` bytes.
+    fn pad(self, mut bytes: Vec<u8>, width: usize) -> Vec<u8> {
+        if bytes.len() >= width {
+            return bytes;
+        }
+        match self {
+            Endianness::Little => bytes.resize(width, 0),
+            Endianness::Big => {
+                bytes.splice(0..0, std::iter::repeat_n(0, width - bytes.len()));
+            }
+        }
+        bytes
+    }
+}
+
+/// Appends the encoding of a big integer to the buffer.
+pub(crate) fn write_biguint(buf: &mut Vec<u8>, x: &BigUint) {
+    write_biguint_in(buf, x, 0, Endianness::Little);
+}
+
+/// Returns the length of the encoding of a big integer written by [write_biguint].
+pub(crate) fn biguint_encoded_len(x: &BigUint) -> usize {
+    // The encoding of zero is a single zero byte.
+    4 + (x.bits().div_ceil(8) as usize).max(1)
+}
+
+/// Appends the encoding of a big integer to the buffer, as [write_biguint] does, with its
+/// bytes padded with zeros to `width` bytes, in the byte order `endianness`. The padding does
+/// not change the decoded value.
+pub(crate) fn write_biguint_in(
+    buf: &mut Vec<u8>,
+    x: &BigUint,
+    width: usize,
+    endianness: Endianness,
+) {
+    let bytes = endianness.pad(endianness.biguint_to_bytes(x), width);
+    write_bytes_in(buf, &bytes, endianness);
+}
+
+/// Appends the bytes prefixed by a 4-byte little-endian length to the buffer.
+pub(crate) fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    write_bytes_in(buf, bytes, Endianness::Little);
+}
+
+/// Appends the bytes prefixed by a 4-byte length in the byte order `endianness` to the buffer.
+pub(crate) fn write_bytes_in(buf: &mut Vec<u8>, bytes: &[u8], endianness: Endianness) {
+    buf.extend_from_slice(&endianness.u32_to_bytes(bytes.len() as u32));
+    buf.extend_from_slice(bytes);
+}
+
+/// Reads the encoded values from a byte slice in order.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    endianness: Endianness,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self::with_endianness(bytes, Endianness::Little)
+    }
+
+    /// Instantiates a reader of the values encoded in the byte order `endianness`.
+    pub(crate) fn with_endianness(bytes: &'a [u8], endianness: Endianness) -> Self {
+        Self { bytes, endianness }
+    }
+
+    /// Reads exactly `len` bytes.
+    fn read_slice(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.bytes.len() < len {
+            return Err(Error::DeserializationFailed);
+        }
+        let (slice, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(slice)
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32, Error> {
+        let slice = self.read_slice(4)?.try_into().unwrap();
+        Ok(match self.endianness {
+            Endianness::Little => u32::from_le_bytes(slice),
+            Endianness::Big => u32::from_be_bytes(slice),
+        })
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<u64, Error> {
+        let slice = self.read_slice(8)?.try_into().unwrap();
+        Ok(match self.endianness {
+            Endianness::Little => u64::from_le_bytes(slice),
+            Endianness::Big => u64::from_be_bytes(slice),
+        })
+    }
+
+    /// Reads bytes prefixed by a 4-byte length.
+    pub(crate) fn read_bytes(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.read_u32()? as usize;
+        self.read_slice(len)
+    }
+
+    /// Reads a big integer, whose length must not exceed [MAX_BIGUINT_LEN].
+    pub(crate) fn read_biguint(&mut self) -> Result<BigUint, Error> {
+        let len = self.read_u32()? as usize;
+        if len > MAX_BIGUINT_LEN {
+            return Err(Error::DeserializationFailed);
+        }
+        let bytes = self.read_slice(len)?;
+        Ok(self.endianness.biguint_from_bytes(bytes))
+    }
+
+    /// Returns true if all of the bytes have been read.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Checks that all of the bytes have been read.
+    pub(crate) fn finish(self) -> Result<(), Error> {
+        if !self.bytes.is_empty() {
+            return Err(Error::DeserializationFailed);
+        }
+        Ok(())
+    }
+}
+
+/// Serializes a big integer as a decimal string, which is human-readable and does not depend
+/// on the internal representation of [BigUint]. Used by `#[serde(with = "...")]`.
+#[cfg(feature = "serde")]
+pub(crate) mod decimal {
+    use std::str::FromStr;
+
+    use num_bigint::BigUint;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(x: &BigUint, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(x)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BigUint, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        BigUint::from_str(&s).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,145 @@
+//! Defines the error types returned by the fallible operations of the crate.
+
+use std::fmt;
+
+/// Errors returned by the protocol steps in the scheme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A value has no multiplicative inverse under the modulus, e.g. it is a multiple of `q`.
+    NonInvertible,
+    /// A generator of the scheme parameters is not an element of the subgroup of order `q`.
+    InvalidGenerator,
+    /// The generators of the scheme parameters are not distinct.
+    DuplicateGenerators,
+    /// The bytes cannot be decoded into the expected value.
+    DeserializationFailed,
+    /// The checksum of an encoded value does not match its content.
+    InvalidChecksum,
+    /// The version of an encoded value is not supported.
+    UnsupportedVersion,
+    /// The coin has already been spent by the spender.
+    AlreadySpent,
+    /// The challenge is not a value in the range `[0, q)`.
+    InvalidChallenge,
+    /// The spender has not received a registration ID from the issuer.
+    NotRegistered,
+    /// The secret key, or a secret value of a coin, is not a value in the range `[1, q)`.
+    InvalidSecret,
+    /// The number of shows of a coin is zero.
+    InvalidShows,
+    /// The show slot is not provisioned in the coin.
+    InvalidSlot,
+    /// The spent coins are spent on different show slots of a k-show coin, which does not
+    /// reveal the identity of the spender.
+    DifferentSlots,
+    /// The spent coin is not valid upon the coin challenge.
+    InvalidSpentCoin,
+    /// The withdrawal parameters of the issuer have been used in another withdrawal.
+    NonceReuse,
+    /// The withdrawal parameters do not belong to the same withdrawal.
+    WithdrawalMismatch,
+    /// The spender is not derived from a seed.
+    NotDeterministic,
+    /// The identity is not an element of the subgroup of order q, or is rejected by the issuer
+    /// (e.g. 1 or the issuer's identity).
+    InvalidIdentity,
+    /// The prime modulus p is not 2q + 1.
+    InvalidParams,
+    /// The coin is not denominated in the expected unit.
+    UnitMismatch,
+    /// The spent coins do not refer to the same coin.
+    DifferentCoins,
+    /// The coin was spent twice upon the same challenge.
+    SameChallenge,
+    /// The coin fails verification for the reason.
+    InvalidCoin(VerifyError),
+    /// The coin fails verification for the reason, under the parameters of the labelled group
+    /// (see [Params::label](crate::Params::label)).
+    InvalidCoinInGroup { label: String, reason: VerifyError },
+    /// A required setting (e.g. the scheme key or the group) is not given to the
+    /// [ParamsBuilder](crate::ParamsBuilder).
+    MissingSetting,
+    /// The message of a coin challenge is empty, which does not bind the challenge to a payment.
+    EmptyMessage,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NonInvertible => write!(f, "value is not invertible under the modulus"),
+            Error::InvalidGenerator => write!(f, "generator is not in the subgroup of order q"),
+            Error::DuplicateGenerators => write!(f, "generators are not distinct"),
+            Error::DeserializationFailed => write!(f, "failed to deserialize"),
+            Error::InvalidChecksum => write!(f, "checksum does not match"),
+            Error::UnsupportedVersion => write!(f, "unsupported version"),
+            Error::AlreadySpent => write!(f, "coin has already been spent"),
+            Error::InvalidChallenge => write!(f, "challenge is out of range"),
+            Error::NotRegistered => write!(f, "spender is not registered"),
+            Error::InvalidSecret => write!(f, "secret is out of range"),
+            Error::InvalidShows => write!(f, "number of shows is zero"),
+            Error::InvalidSlot => write!(f, "show slot is not provisioned in the coin"),
+            Error::DifferentSlots => write!(f, "coins are spent on different show slots"),
+            Error::InvalidSpentCoin => write!(f, "spent coin is not valid"),
+            Error::NonceReuse => write!(f, "withdrawal parameters are reused"),
+            Error::WithdrawalMismatch => write!(f, "withdrawal parameters do not match"),
+            Error::NotDeterministic => write!(f, "spender is not derived from a seed"),
+            Error::InvalidIdentity => write!(f, "identity is not a subgroup element"),
+            Error::InvalidParams => write!(f, "prime modulus does not match subgroup order"),
+            Error::UnitMismatch => write!(f, "coin is not denominated in the unit"),
+            Error::DifferentCoins => write!(f, "spent coins refer to different coins"),
+            Error::SameChallenge => write!(f, "coin is spent upon the same challenge"),
+            Error::InvalidCoin(err) => write!(f, "coin is invalid: {}", err),
+            Error::InvalidCoinInGroup { label, reason } => {
+                write!(f, "coin is invalid in group {}: {}", label, reason)
+            }
+            Error::MissingSetting => write!(f, "params builder is missing a setting"),
+            Error::EmptyMessage => write!(f, "message of the challenge is empty"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<VerifyError> for Error {
+    fn from(err: VerifyError) -> Self {
+        Error::InvalidCoin(err)
+    }
+}
+
+/// Reasons for a coin, or a spent coin, failing verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The coin is degenerate (i.e. `c1`, `c2` or `c3` is 1) and cannot be traced to a spender.
+    DegenerateCoin,
+    /// The challenge `cd` carried by the coin does not match the hash of its content.
+    ChallengeMismatch,
+    /// The verification equations of the issuer's signature on the coin do not hold.
+    SignatureEqnFailed,
+    /// The responses of the spent coin do not satisfy the coin challenge.
+    ResponseEqnFailed,
+    /// The metadata bound into the coin is not the one claimed.
+    MetadataMismatch,
+    /// The identity of the issuer (h) is not an element of the subgroup of order `q` other than 1.
+    InvalidIssuerKey,
+    /// The bytes cannot be decoded into a coin.
+    MalformedCoin,
+    /// The coin is in the legacy format (i.e. `cd` is not reduced mod q), which is not accepted.
+    LegacyFormat,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::DegenerateCoin => write!(f, "coin is degenerate"),
+            VerifyError::ChallengeMismatch => write!(f, "coin challenge does not match"),
+            VerifyError::SignatureEqnFailed => write!(f, "coin signature equation fails"),
+            VerifyError::ResponseEqnFailed => write!(f, "spent coin response equation fails"),
+            VerifyError::MetadataMismatch => write!(f, "coin metadata does not match"),
+            VerifyError::InvalidIssuerKey => write!(f, "issuer key is invalid"),
+            VerifyError::MalformedCoin => write!(f, "coin is malformed"),
+            VerifyError::LegacyFormat => write!(f, "coin is in the legacy format"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
@@ -1,12 +1,24 @@
 //! Implements of the protocol steps involved by an Issuer in the scheme.
 
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
 use num_bigint::BigUint;
+use rand::RngCore;
 
 use crate::{
-    cryptographics::random_number,
+    coin::{CoinChallenge, CoinFingerprint, SpentCoin},
+    cryptographics::{batch_inv_mod, random_nonzero, random_number_from, schnorr_sign},
+    ledger::{DepositLedger, DepositOutcome},
     params::Params,
+    receipt::Receipt,
+    refresh::RefreshRequest,
+    telemetry,
+    verifier::key_commitment,
     withdrawal::{WithdrawalChallenge, WithdrawalResponse},
-    Identity, RegistrationID, WithdrawalParams, WithdrawalResponseParams,
+    Error, Identity, RegistrationID, WithdrawalParams, WithdrawalResponseParams,
 };
 
 /// A mathematic representation of a coin issuer in the scheme, which implements
@@ -21,14 +33,58 @@ pub struct Issuer {
     ///
     /// (x, H) key pair by issuer, x is secret key
     x: BigUint,
+    /// The values `a = g^w` of the withdrawal parameters that have been responded to, for
+    /// rejecting the reused parameters.
+    used_nonces: Mutex<HashSet<BigUint>>,
 }
 
 impl Issuer {
     pub fn new(params: Params) -> Self {
-        let x = random_number(&params.q);
+        Self::new_with_rng(params, &mut rand::thread_rng())
+    }
+
+    /// Instantiates an issuer as [Issuer::new] does, with the secret key sampled from `rng`
+    /// (e.g. `&mut dyn RngCore` supplied by a WASM host where `rand::thread_rng` is not
+    /// available). The withdrawal parameters can be set up with the same `rng` by
+    /// [setup_withdrawal_params_many](crate::Issuer::setup_withdrawal_params_many).
+    pub fn new_with_rng<R: RngCore + ?Sized>(params: Params, rng: &mut R) -> Self {
+        let x = random_nonzero(&params.q, rng);
         // H = g^x
         let h = params.g.modpow(&x, &params.p);
-        Self { params, h, x }
+        Self {
+            params,
+            h,
+            x,
+            used_nonces: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Restores an issuer from the persisted secret key `x` (e.g. held in an HSM), e.g. after
+    /// the issuer process restarts. The identity of the issuer is recomputed from `x`, so the
+    /// coins issued before remain valid. The withdrawal parameters responded to before the
+    /// restart are not remembered.
+    ///
+    /// Returns [Error::InvalidSecret] if `x` is not in the range `[1, q)`.
+    pub fn from_secret(params: Params, x: BigUint) -> Result<Self, Error> {
+        if x == BigUint::ZERO || x >= params.q {
+            return Err(Error::InvalidSecret);
+        }
+        // H = g^x
+        let h = params.g.modpow(&x, &params.p);
+        Ok(Self {
+            params,
+            h,
+            x,
+            used_nonces: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// Returns the commitment of the identity of the issuer (h), i.e. a domain-separated hash of
+    /// `h` and the parameters, which the issuer can publish (e.g. in a certificate) so that the
+    /// receivers can check the `h` handed to them by
+    /// [CoinVerifier::check_issuer_commitment](crate::CoinVerifier::check_issuer_commitment).
+    pub fn key_commitment(&self) -> [u8; 32] {
+        key_commitment(&self.h, &self.params)
     }
 
     /// Registers for opening an account to a spender, and gives back the
@@ -38,9 +94,18 @@ impl Issuer {
     /// There is an attack to the scheme involving a bad user manipulating a false value of `i`
     /// who can later double spend without being caught.
     /// It is necessary for the issuer to ensture the authentication of the registration process.
-    pub fn register(&self, i: &Identity) -> RegistrationID {
+    ///
+    /// Returns [Error::InvalidIdentity] if `i` is not an element of the subgroup of order q, or
+    /// is 1 or the issuer's identity (h). Such an identity is never derived by an honest spender,
+    /// and degenerates the verification equations (e.g. the registration ID of `i = h` is
+    /// `(h * g2)^x`, which binds the spender to the issuer's own key).
+    pub fn register(&self, i: &Identity) -> Result<RegistrationID, Error> {
+        if *i == BigUint::from(1u64) || *i == self.h || !self.params.is_subgroup_element(i) {
+            return Err(Error::InvalidIdentity);
+        }
+        telemetry::count(telemetry::REGISTER, 1);
         // z = (I * g2)^x
-        (i * &self.params.g2).modpow(&self.x, &self.params.p)
+        Ok((i * &self.params.g2).modpow(&self.x, &self.params.p))
     }
 
     /// Setting up the parameters for starting the withdrawal process which issues one
@@ -52,23 +117,264 @@ impl Issuer {
         &self,
         i: &Identity,
     ) -> (WithdrawalParams, WithdrawalResponseParams) {
-        let w = random_number(&self.params.q);
-        // a = g^w
-        let a = self.params.g.modpow(&w, &self.params.p);
-        // b = (i * g2)^w
-        let b = (i * &self.params.g2).modpow(&w, &self.params.p);
-        (WithdrawalParams { a, b }, WithdrawalResponseParams { w })
+        self.setup_withdrawal_params_with_rng(i, &mut rand::thread_rng())
+    }
+
+    /// Sets up the parameters as
+    /// [setup_withdrawal_params](crate::Issuer::setup_withdrawal_params) does, with the secret
+    /// `w` sampled from `rng` (e.g. `&mut dyn RngCore` supplied by a WASM host).
+    pub fn setup_withdrawal_params_with_rng<R: RngCore + ?Sized>(
+        &self,
+        i: &Identity,
+        rng: &mut R,
+    ) -> (WithdrawalParams, WithdrawalResponseParams) {
+        self.setup_withdrawal_params_many(i, 1, rng).pop().unwrap()
+    }
+
+    /// Sets up the parameters for `n` withdrawals by the same spender at once, as
+    /// [setup_withdrawal_params](crate::Issuer::setup_withdrawal_params) does for each of them,
+    /// with the `w` values sampled independently from `rng`. The base `(i * g2)` is computed
+    /// only once for all of the withdrawals.
+    pub fn setup_withdrawal_params_many<R: RngCore + ?Sized>(
+        &self,
+        i: &Identity,
+        n: usize,
+        rng: &mut R,
+    ) -> Vec<(WithdrawalParams, WithdrawalResponseParams)> {
+        let i_g2 = (i * &self.params.g2) % &self.params.p;
+        (0..n)
+            .map(|_| {
+                let w = random_number_from(rng, &self.params.q);
+                // a = g^w
+                let a = self.params.g.modpow(&w, &self.params.p);
+                // b = (i * g2)^w
+                let b = i_g2.modpow(&w, &self.params.p);
+                (
+                    WithdrawalParams { a: a.clone(), b },
+                    WithdrawalResponseParams { w, a },
+                )
+            })
+            .collect()
     }
 
     /// Returns a response to the spender in withdrawal process. The response will then be used by
     /// spender to make a coin.
+    ///
+    /// Returns [Error::NonceReuse] if the withdrawal parameters have been responded to before,
+    /// because two responses with the same `w` reveal the secret key of the issuer. The issuer
+    /// remembers the parameters of all the withdrawals it responded to.
     pub fn withdrawal_response(
         &self,
         withdrawal: WithdrawalResponseParams,
         challenge: &WithdrawalChallenge,
+    ) -> Result<WithdrawalResponse, Error> {
+        if !self
+            .used_nonces
+            .lock()
+            .unwrap()
+            .insert(withdrawal.a.clone())
+        {
+            return Err(Error::NonceReuse);
+        }
+        Ok(self.respond(&withdrawal, challenge))
+    }
+
+    /// Computes the response to the withdrawal, whose nonce is assumed to be marked as used.
+    fn respond(
+        &self,
+        withdrawal: &WithdrawalResponseParams,
+        challenge: &WithdrawalChallenge,
     ) -> WithdrawalResponse {
         // r = w + c*x mod q
         let r = (&withdrawal.w + &challenge.c * &self.x) % &self.params.q;
+        telemetry::count(telemetry::WITHDRAWAL, 1);
         WithdrawalResponse { r }
     }
+
+    /// Returns a response to the spender in withdrawal process, as
+    /// [withdrawal_response](crate::Issuer::withdrawal_response) does, after checking that the
+    /// request is well-formed, so that a malformed request is detected early.
+    ///
+    /// Returns [Error::InvalidChallenge] if the challenge is not in the range `[0, q)`, or
+    /// [Error::WithdrawalMismatch] if the public parameters `withdrawal_params` given to the
+    /// spender do not belong to the secret `withdrawal`.
+    pub fn withdrawal_response_checked(
+        &self,
+        withdrawal_params: &WithdrawalParams,
+        withdrawal: WithdrawalResponseParams,
+        challenge: &WithdrawalChallenge,
+    ) -> Result<WithdrawalResponse, Error> {
+        if challenge.c >= self.params.q {
+            return Err(Error::InvalidChallenge);
+        }
+        // a = g^w
+        if withdrawal_params.a != withdrawal.a
+            || self.params.g.modpow(&withdrawal.w, &self.params.p) != withdrawal.a
+        {
+            return Err(Error::WithdrawalMismatch);
+        }
+        self.withdrawal_response(withdrawal, challenge)
+    }
+
+    /// Refreshes a coin upon the request from the spender, by depositing the old coin into the
+    /// ledger and returning the response to the withdrawal of the new coin, as
+    /// [withdrawal_response](crate::Issuer::withdrawal_response) does. The response is returned
+    /// only if the old coin is deposited for the first time, so the old coin cannot be spent or
+    /// refreshed again without being detected by the ledger.
+    ///
+    /// The request is checked before the old coin is deposited, so that the old coin is left
+    /// unspent in the ledger if an error is returned.
+    ///
+    /// Returns [Error::InvalidSpentCoin] if the old coin is not issued by the issuer or not
+    /// spent upon the challenge of the request, [Error::InvalidChallenge] if the challenge of
+    /// the withdrawal is not in the range `[0, q)`, [Error::NonceReuse] if the withdrawal
+    /// parameters have been responded to before, [Error::AlreadySpent] if the old coin has been
+    /// deposited into the ledger before, or the errors of [DepositLedger::deposit].
+    pub fn refresh(
+        &self,
+        request: &RefreshRequest,
+        ledger: &mut DepositLedger,
+        withdrawal: WithdrawalResponseParams,
+    ) -> Result<WithdrawalResponse, Error> {
+        let challenge = request.challenge();
+        if !request.spent_coin.coin.verify(&self.h, &self.params)
+            || !request.spent_coin.verify(&challenge, &self.params)
+        {
+            return Err(Error::InvalidSpentCoin);
+        }
+        if request.withdrawal_challenge.c >= self.params.q {
+            return Err(Error::InvalidChallenge);
+        }
+        // The nonces are locked until the old coin is deposited, so that the nonce cannot be
+        // used by another withdrawal in between.
+        let mut used_nonces = self.used_nonces.lock().unwrap();
+        if used_nonces.contains(&withdrawal.a) {
+            return Err(Error::NonceReuse);
+        }
+        match ledger.deposit(request.spent_coin.clone(), challenge)? {
+            DepositOutcome::Accepted => {
+                used_nonces.insert(withdrawal.a.clone());
+                drop(used_nonces);
+                Ok(self.respond(&withdrawal, &request.withdrawal_challenge))
+            }
+            DepositOutcome::Duplicate | DepositOutcome::DoubleSpend(_) => Err(Error::AlreadySpent),
+        }
+    }
+
+    /// Issues a receipt to the receiver who deposits the spent coin, which is a signature of the
+    /// issuer over the fingerprint of the coin and the amount credited (i.e. the denomination in
+    /// the metadata of the coin, or 0 if the coin carries no metadata). The receiver can keep
+    /// the receipt as a proof of credit, and verify it by [Receipt::verify].
+    ///
+    /// Returns [Error::InvalidSpentCoin] if the spent coin is not issued by the issuer, or not
+    /// valid upon the coin challenge.
+    pub fn issue_receipt(
+        &self,
+        spent_coin: &SpentCoin,
+        challenge: &CoinChallenge,
+    ) -> Result<Receipt, Error> {
+        self.issue_receipt_with_rng(spent_coin, challenge, &mut rand::thread_rng())
+    }
+
+    /// Issues a receipt as [issue_receipt](crate::Issuer::issue_receipt) does, with the nonce of
+    /// the signature sampled from `rng`.
+    pub fn issue_receipt_with_rng<R: RngCore + ?Sized>(
+        &self,
+        spent_coin: &SpentCoin,
+        challenge: &CoinChallenge,
+        rng: &mut R,
+    ) -> Result<Receipt, Error> {
+        if !spent_coin.coin.verify(&self.h, &self.params)
+            || !spent_coin.verify(challenge, &self.params)
+        {
+            return Err(Error::InvalidSpentCoin);
+        }
+        Ok(self.sign_receipt(spent_coin, rng))
+    }
+
+    /// Signs the receipt of the spent coin, which is assumed to be verified.
+    pub(crate) fn sign_receipt<R: RngCore + ?Sized>(
+        &self,
+        spent_coin: &SpentCoin,
+        rng: &mut R,
+    ) -> Receipt {
+        let fingerprint = spent_coin.coin.fingerprint();
+        let amount = spent_coin
+            .coin
+            .metadata
+            .as_ref()
+            .map_or(0, |metadata| metadata.denomination);
+        let (e, s) = schnorr_sign(
+            &self.x,
+            &self.params.g,
+            &self.params.p,
+            &self.params.q,
+            rng,
+            |r| Receipt::challenge(&self.params, &self.h, r, &fingerprint, amount),
+        );
+        Receipt {
+            fingerprint,
+            amount,
+            e,
+            s,
+        }
+    }
+
+    /// Reveals the identities of the double spenders among the deposited spent coins, as
+    /// [reveal_identity](crate::SpentCoin::reveal_identity) does for a pair of spent coins.
+    ///
+    /// The spent coins are grouped by the fingerprints of the coins and the show slots. An
+    /// identity is revealed for each group that contains spent coins with different responses,
+    /// while the slots used only once are skipped. The modular inversions of all groups are computed in a
+    /// batch. The results are in the order of the first appearance of the coins.
+    ///
+    /// The spent coins are assumed to be verified upon their coin challenges.
+    pub fn reveal_identities(
+        &self,
+        spent_coins: &[SpentCoin],
+    ) -> Result<Vec<(CoinFingerprint, Identity)>, Error> {
+        let q = &self.params.q;
+
+        let mut groups: Vec<(CoinFingerprint, Vec<&SpentCoin>)> = Vec::new();
+        let mut group_indices = HashMap::new();
+        for spent_coin in spent_coins {
+            let fingerprint = spent_coin.coin.fingerprint();
+            let key = (fingerprint, spent_coin.slot);
+            let index = *group_indices.entry(key).or_insert_with(|| {
+                groups.push((fingerprint, Vec::new()));
+                groups.len() - 1
+            });
+            groups[index].1.push(spent_coin);
+        }
+
+        // Differences of the responses between the first spent coin and the first one with
+        // different responses in each group.
+        let mut fingerprints = Vec::new();
+        let mut r1_diffs = Vec::new();
+        let mut r2_diffs = Vec::new();
+        for (fingerprint, group) in &groups {
+            let first = group[0];
+            let diffs = group[1..]
+                .iter()
+                .map(|other| first.response_diffs(other, q))
+                .find(|(_, r2_diff)| *r2_diff != BigUint::ZERO);
+            if let Some((r1_diff, r2_diff)) = diffs {
+                fingerprints.push(*fingerprint);
+                r1_diffs.push(r1_diff);
+                r2_diffs.push(r2_diff);
+            }
+        }
+
+        // g1 ^ ( (r1-r1') / (r2-r2') )
+        let r2_diff_invs = batch_inv_mod(&r2_diffs, q)?;
+        telemetry::count(telemetry::DOUBLE_SPEND_DETECTED, fingerprints.len() as u64);
+        Ok(fingerprints
+            .into_iter()
+            .zip(r1_diffs.iter().zip(r2_diff_invs.iter()))
+            .map(|(fingerprint, (r1_diff, r2_diff_inv))| {
+                let exponent = (r1_diff * r2_diff_inv) % q;
+                (fingerprint, self.params.g1_pow(&exponent))
+            })
+            .collect())
+    }
 }
@@ -0,0 +1,144 @@
+//! Defines the ledger used by an issuer for detecting double spent coins upon deposits.
+
+use std::{collections::HashMap, sync::Mutex, time::SystemTime};
+
+use num_bigint::BigUint;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    coin::{CoinChallenge, CoinFingerprint, SpentCoin},
+    params::Params,
+    Error, Identity,
+};
+
+/// The outcome of depositing a spent coin into a [DepositLedger].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DepositOutcome {
+    /// The coin is deposited for the first time.
+    Accepted,
+    /// The same spent coin (i.e. same coin and responses) has been deposited before.
+    Duplicate,
+    /// The coin has been spent before upon another challenge. The identity of the double
+    /// spender is revealed in the event.
+    DoubleSpend(DoubleSpendEvent),
+}
+
+/// A record of a double spent coin detected by a [DepositLedger], for auditing by operators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DoubleSpendEvent {
+    /// The time when the double spending is detected.
+    pub timestamp: SystemTime,
+    /// The fingerprint of the double spent coin.
+    pub fingerprint: CoinFingerprint,
+    /// The revealed identity of the double spender.
+    pub identity: Identity,
+    /// The coin challenges of the deposited spent coin and the double spent one, in order.
+    pub challenges: [CoinChallenge; 2],
+}
+
+/// A registry of the spent coins deposited to an issuer, which detects double spent coins
+/// and reveals the identities of the double spenders.
+pub struct DepositLedger {
+    /// The public scheme parameters.
+    params: Params,
+    deposits: HashMap<CoinFingerprint, Vec<(SpentCoin, CoinChallenge)>>,
+}
+
+impl DepositLedger {
+    pub fn new(params: Params) -> Self {
+        Self {
+            params,
+            deposits: HashMap::new(),
+        }
+    }
+
+    /// Deposits the spent coin with the coin challenge upon which it was spent. The spent coin
+    /// is assumed to be verified upon the coin challenge before depositing.
+    ///
+    /// The deposits are looked up by the fingerprint of the coin, so the timing of the deposit
+    /// leaks whether the coin has been deposited before. Only the comparisons between the spent
+    /// coins of the same coin (i.e. the show slots and the responses) are in constant time.
+    pub fn deposit(
+        &mut self,
+        mut spent_coin: SpentCoin,
+        challenge: CoinChallenge,
+    ) -> Result<DepositOutcome, Error> {
+        // The unreduced responses would not be found as a duplicate of the reduced ones.
+        spent_coin.normalize(&self.params.q);
+        let fingerprint = spent_coin.coin.fingerprint();
+        let deposits = self.deposits.entry(fingerprint).or_default();
+
+        // Only the coins spent on the same show slot reveal the identity of the spender.
+        let same_slot = |(d, _): &&(SpentCoin, CoinChallenge)| {
+            bool::from(d.ct_eq(&spent_coin) & d.slot.ct_eq(&spent_coin.slot))
+        };
+        let mut double_spent_coin = None;
+        for deposited in deposits.iter().filter(same_slot) {
+            if bool::from(deposited.0.ct_eq_responses(&spent_coin)) {
+                return Ok(DepositOutcome::Duplicate);
+            }
+            double_spent_coin.get_or_insert(deposited);
+        }
+
+        let outcome = match double_spent_coin {
+            Some((deposited, deposited_challenge)) => {
+                DepositOutcome::DoubleSpend(DoubleSpendEvent {
+                    timestamp: SystemTime::now(),
+                    fingerprint,
+                    identity: deposited.reveal_identity(&spent_coin, &self.params)?,
+                    challenges: [deposited_challenge.clone(), challenge.clone()],
+                })
+            }
+            None => DepositOutcome::Accepted,
+        };
+        deposits.push((spent_coin, challenge));
+        Ok(outcome)
+    }
+}
+
+/// Number of shards of a [ConcurrentDepositLedger] created by [ConcurrentDepositLedger::new].
+const DEFAULT_SHARDS: usize = 16;
+
+/// A [DepositLedger] which can be shared by multiple threads (e.g. the deposit handlers of a
+/// server) without external locking. The spent coins are partitioned into shards by the
+/// fingerprints of the coins, each of which is guarded by its own lock, so that the deposits of
+/// different coins rarely contend. The deposits of the same coin are serialized by the lock of
+/// the shard, so that a double spending is detected exactly once.
+pub struct ConcurrentDepositLedger {
+    /// The order of the subgroup, for normalizing the spent coins before choosing the shards.
+    q: BigUint,
+    shards: Vec<Mutex<DepositLedger>>,
+}
+
+impl ConcurrentDepositLedger {
+    pub fn new(params: Params) -> Self {
+        Self::with_shards(params, DEFAULT_SHARDS)
+    }
+
+    /// Instantiates the ledger with the number of shards, which is at least 1.
+    pub fn with_shards(params: Params, shards: usize) -> Self {
+        Self {
+            q: params.q.clone(),
+            shards: (0..shards.max(1))
+                .map(|_| Mutex::new(DepositLedger::new(params.clone())))
+                .collect(),
+        }
+    }
+
+    /// Deposits the spent coin with the coin challenge upon which it was spent, as
+    /// [DepositLedger::deposit] does.
+    pub fn deposit(
+        &self,
+        mut spent_coin: SpentCoin,
+        challenge: CoinChallenge,
+    ) -> Result<DepositOutcome, Error> {
+        // The same coin must be deposited into the same shard however it is encoded.
+        spent_coin.normalize(&self.q);
+        let fingerprint = spent_coin.coin.fingerprint();
+        // The fingerprint is a hash, so its leading bytes are uniformly distributed.
+        let index = u64::from_le_bytes(fingerprint.0[..8].try_into().unwrap());
+        let shard = &self.shards[(index % self.shards.len() as u64) as usize];
+        shard.lock().unwrap().deposit(spent_coin, challenge)
+    }
+}
@@ -1,19 +1,81 @@
+#[cfg(feature = "async")]
+mod async_issuer;
+#[cfg(feature = "async")]
+pub use async_issuer::{AsyncIssuer, BlockingIssuer};
+
 mod coin;
-pub use coin::{Coin, CoinChallenge, PartialCoin, SpentCoin};
+pub use coin::{
+    Coin, CoinChallenge, CoinFingerprint, CoinFormat, CoinMetadata, CoinWarning, CoinWithSecrets,
+    PartialCoin, SpentCoin,
+};
 
 mod cryptographics;
 
+mod denomination;
+pub use denomination::{DenominatedLedger, DenominatedSpentCoin, Denomination, Unit};
+
+mod encoding;
+pub use encoding::Endianness;
+
+mod error;
+pub use error::{Error, VerifyError};
+
 mod issuer;
 pub use issuer::Issuer;
 
+mod ledger;
+pub use ledger::{ConcurrentDepositLedger, DepositLedger, DepositOutcome, DoubleSpendEvent};
+
+pub mod math;
+
+/// Exercises the scheme without the `serde` feature, e.g. by `cargo test --no-default-features`.
+#[cfg(all(test, not(feature = "serde")))]
+mod no_serde;
+
+mod onetime;
+pub use onetime::TransactionSignature;
+
 mod params;
 pub use params::*;
 
+mod proof;
+pub use proof::CoinProof;
+
+mod receipt;
+pub use receipt::Receipt;
+
+mod receiver;
+pub use receiver::Receiver;
+
+mod refresh;
+pub use refresh::RefreshRequest;
+
+mod research;
+pub use research::ResearchRecord;
+
+mod session;
+pub use session::{WithdrawalChallenged, WithdrawalReady, WithdrawalSession};
+
+mod settlement;
+pub use settlement::{Settlement, SettlementResult};
+
 mod spender;
 pub use spender::Spender;
 
+mod telemetry;
+
+mod transcript;
+pub use transcript::SpendTranscript;
+
 mod types;
 pub use types::*;
 
+mod verifier;
+pub use verifier::{
+    verify_serialized_coin, CoinVerifier, IssuerKeyRing, PrecomputedVerifier, VerifyCoin,
+};
+
 mod withdrawal;
-pub use withdrawal::{Withdrawal, WithdrawalParams, WithdrawalResponse, WithdrawalResponseParams};
+pub use withdrawal::{
+    Withdrawal, WithdrawalChallenge, WithdrawalParams, WithdrawalResponse, WithdrawalResponseParams,
+};
@@ -0,0 +1,260 @@
+//! Modular arithmetic helpers used by the scheme, which are also useful for the applications
+//! working with the values of the scheme (e.g. verifiers implemented outside of the library).
+
+use num_bigint::BigUint;
+use subtle::{Choice, ConditionallySelectable};
+
+use crate::Error;
+
+/// Returns `a - b (mod m)`, wrapping around if `a < b`. The inputs are not required to be
+/// reduced modulo `m`.
+///
+/// The reduced inputs are subtracted over their limbs padded to the width of `m`, and `m` is
+/// added back by a constant-time selection instead of a branch, so that the timing does not
+/// leak which of the inputs is greater (e.g. for the responses of the spent coins in
+/// [SpentCoin::reveal_identity](crate::SpentCoin::reveal_identity)).
+pub fn sub_mod(a: &BigUint, b: &BigUint, m: &BigUint) -> BigUint {
+    let width = m.to_u32_digits().len();
+    let a = limbs(&(a % m), width);
+    let b = limbs(&(b % m), width);
+    let m = limbs(m, width);
+
+    // d = a - b, which wraps around if a < b
+    let mut d = vec![0u32; width];
+    let mut borrow = 0u32;
+    for i in 0..width {
+        let (x, b1) = a[i].overflowing_sub(b[i]);
+        let (x, b2) = x.overflowing_sub(borrow);
+        d[i] = x;
+        borrow = (b1 | b2) as u32;
+    }
+    // d + m, which is the result if a < b
+    let underflow = Choice::from(borrow as u8);
+    let mut carry = 0u32;
+    for i in 0..width {
+        let (x, c1) = d[i].overflowing_add(m[i]);
+        let (x, c2) = x.overflowing_add(carry);
+        carry = (c1 | c2) as u32;
+        d[i] = u32::conditional_select(&d[i], &x, underflow);
+    }
+    BigUint::new(d)
+}
+
+/// Returns the little-endian 32-bit limbs of `x`, padded to `width` limbs.
+fn limbs(x: &BigUint, width: usize) -> Vec<u32> {
+    let mut limbs = x.to_u32_digits();
+    limbs.resize(width, 0);
+    limbs
+}
+
+/// Returns true if `x` is an element of the subgroup of order `q` (mod p), i.e. `0 < x < p`
+/// and `x^q = 1 (mod p)`.
+pub fn is_subgroup_element(x: &BigUint, q: &BigUint, p: &BigUint) -> bool {
+    *x > BigUint::ZERO && x < p && x.modpow(q, p) == BigUint::from(1u64)
+}
+
+/// Returns the Jacobi symbol `(a / n)` for the odd `n`, i.e. 1, -1, or 0 if `a` and `n` are not
+/// coprime. It is computed by the law of quadratic reciprocity, which takes a number of
+/// divisions linear in the bits of `n` rather than a modular exponentiation.
+///
+/// ## Panics
+/// Panics if `n` is even.
+pub fn jacobi(a: &BigUint, n: &BigUint) -> i8 {
+    assert!(n.bit(0), "the modulus of the Jacobi symbol must be odd");
+    // The lowest 3 bits of the value.
+    let low = |x: &BigUint| x.iter_u32_digits().next().unwrap_or(0) & 7;
+    let mut a = a % n;
+    let mut n = n.clone();
+    let mut result = 1;
+    while a != BigUint::ZERO {
+        // (2 / n) = -1 if n = 3, 5 (mod 8)
+        let twos = a.trailing_zeros().unwrap_or(0);
+        a >>= twos;
+        if twos % 2 == 1 && matches!(low(&n), 3 | 5) {
+            result = -result;
+        }
+        // (a / n) = -(n / a) if a = n = 3 (mod 4)
+        std::mem::swap(&mut a, &mut n);
+        if low(&a) & 3 == 3 && low(&n) & 3 == 3 {
+            result = -result;
+        }
+        a %= &n;
+    }
+    if n == BigUint::from(1u64) {
+        result
+    } else {
+        0
+    }
+}
+
+/// Returns true if `x` is a quadratic residue modulo the odd prime `p` other than 0, i.e.
+/// `0 < x < p` and the Jacobi symbol `(x / p)` is 1. For the safe prime `p = 2q + 1`, the
+/// quadratic residues are exactly the elements of the subgroup of order `q`, so that this is a
+/// faster equivalent of [is_subgroup_element].
+pub fn is_quadratic_residue(x: &BigUint, p: &BigUint) -> bool {
+    *x > BigUint::ZERO && x < p && jacobi(x, p) == 1
+}
+
+/// Returns true if `x` generates the subgroup of order `q` (mod p), i.e. `x` is an element of
+/// the subgroup other than 1 whose order is not 2. As `q` is prime, the order of `x` is then
+/// exactly `q`, rather than a factor of `q`.
+pub fn is_subgroup_generator(x: &BigUint, q: &BigUint, p: &BigUint) -> bool {
+    let one = BigUint::from(1u64);
+    *x != one && x.modpow(&BigUint::from(2u64), p) != one && is_subgroup_element(x, q, p)
+}
+
+/// Returns the multiplicative inverse of `x` (mod `m`), or [Error::NonInvertible] if the
+/// inverse does not exist (e.g. `x` is a multiple of `m`).
+pub fn modinv_checked(x: &BigUint, m: &BigUint) -> Result<BigUint, Error> {
+    x.modinv(m).ok_or(Error::NonInvertible)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sub_mod() {
+        let m = BigUint::from(11u64);
+        let sub = |a: u64, b: u64| sub_mod(&BigUint::from(a), &BigUint::from(b), &m);
+        assert_eq!(sub(7, 3), BigUint::from(4u64));
+        assert_eq!(sub(3, 7), BigUint::from(7u64));
+        assert_eq!(sub(5, 5), BigUint::ZERO);
+        assert_eq!(sub(0, 1), BigUint::from(10u64));
+        // unreduced inputs
+        assert_eq!(sub(14, 2), BigUint::from(1u64));
+        assert_eq!(sub(2, 14), BigUint::from(10u64));
+        assert_eq!(sub(22, 0), BigUint::ZERO);
+    }
+
+    #[test]
+    fn test_sub_mod_matches_branching() {
+        use rand::{Rng, RngCore};
+
+        // The subtraction which branches on the comparison of the reduced inputs.
+        let branching = |a: &BigUint, b: &BigUint, m: &BigUint| {
+            let (a, b) = (a % m, b % m);
+            if a >= b {
+                a - b
+            } else {
+                m - (b - a)
+            }
+        };
+        let mut rng = rand::thread_rng();
+        let mut random = |len: usize| {
+            let mut bytes = vec![0u8; len];
+            rng.fill_bytes(&mut bytes);
+            BigUint::from_bytes_le(&bytes)
+        };
+        for _ in 0..1000 {
+            let m = random(40) | BigUint::from(1u64);
+            let (a, b) = (random(40), random(48));
+            assert_eq!(sub_mod(&a, &b, &m), branching(&a, &b, &m));
+            assert_eq!(sub_mod(&b, &a, &m), branching(&b, &a, &m));
+            assert_eq!(sub_mod(&a, &a, &m), BigUint::ZERO);
+        }
+        let m = BigUint::from(u32::MAX) + 2u64;
+        for _ in 0..1000 {
+            let a = BigUint::from(rng.gen::<u64>());
+            let b = BigUint::from(rng.gen::<u64>());
+            assert_eq!(sub_mod(&a, &b, &m), branching(&a, &b, &m));
+        }
+    }
+
+    #[test]
+    fn test_is_subgroup_element() {
+        // p = 23 = 2 * 11 + 1, the squares form the subgroup of order q = 11.
+        let p = BigUint::from(23u64);
+        let q = BigUint::from(11u64);
+        let is_element = |x: u64| is_subgroup_element(&BigUint::from(x), &q, &p);
+        assert!(is_element(1));
+        assert!(is_element(4));
+        assert!(is_element(9));
+        assert!(!is_element(0));
+        assert!(!is_element(5));
+        assert!(!is_element(22));
+        assert!(!is_element(23));
+        assert!(!is_element(27));
+    }
+
+    #[test]
+    fn test_is_subgroup_generator() {
+        let p = BigUint::from(23u64);
+        let q = BigUint::from(11u64);
+        let is_generator = |x: u64| is_subgroup_generator(&BigUint::from(x), &q, &p);
+        assert!(is_generator(4));
+        assert!(is_generator(9));
+        assert!(!is_generator(0));
+        assert!(!is_generator(1));
+        assert!(!is_generator(5));
+        // order 2
+        assert!(!is_generator(22));
+    }
+
+    #[test]
+    fn test_jacobi() {
+        // Squares modulo 23: 1, 2, 3, 4, 6, 8, 9, 12, 13, 16, 18
+        let p = BigUint::from(23u64);
+        let residues = [1u64, 2, 3, 4, 6, 8, 9, 12, 13, 16, 18];
+        for x in 1..23u64 {
+            let expected = if residues.contains(&x) { 1 } else { -1 };
+            assert_eq!(jacobi(&BigUint::from(x), &p), expected, "{}", x);
+        }
+        assert_eq!(jacobi(&BigUint::ZERO, &p), 0);
+        assert_eq!(jacobi(&BigUint::from(46u64), &p), 0);
+        // (2 / 15) = 1 while 2 is not a square modulo 15.
+        assert_eq!(jacobi(&BigUint::from(2u64), &BigUint::from(15u64)), 1);
+        assert_eq!(jacobi(&BigUint::from(6u64), &BigUint::from(15u64)), 0);
+    }
+
+    #[test]
+    fn test_is_quadratic_residue_matches_subgroup() {
+        use rand::RngCore;
+
+        let p = BigUint::from(23u64);
+        let q = BigUint::from(11u64);
+        for x in 0..50u64 {
+            let x = BigUint::from(x);
+            assert_eq!(
+                is_quadratic_residue(&x, &p),
+                is_subgroup_element(&x, &q, &p)
+            );
+        }
+
+        // p = 2q + 1 of 127 bits
+        let q = BigUint::from(0x4000_0000_0000_0000_0000_0000_0000_112fu128);
+        let p: BigUint = &q * 2u64 + 1u64;
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let mut bytes = [0u8; 16];
+            rng.fill_bytes(&mut bytes);
+            let x = BigUint::from_bytes_le(&bytes) % &p;
+            assert_eq!(
+                is_quadratic_residue(&x, &p),
+                is_subgroup_element(&x, &q, &p)
+            );
+        }
+    }
+
+    #[test]
+    fn test_modinv_checked() {
+        let m = BigUint::from(11u64);
+        assert_eq!(
+            modinv_checked(&BigUint::from(3u64), &m),
+            Ok(BigUint::from(4u64))
+        );
+        assert_eq!(
+            modinv_checked(&BigUint::from(1u64), &m),
+            Ok(BigUint::from(1u64))
+        );
+        assert_eq!(
+            modinv_checked(&BigUint::ZERO, &m),
+            Err(Error::NonInvertible)
+        );
+        assert_eq!(modinv_checked(&m, &m), Err(Error::NonInvertible));
+        assert_eq!(
+            modinv_checked(&BigUint::from(4u64), &BigUint::from(8u64)),
+            Err(Error::NonInvertible)
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Tests of the scheme built without the `serde` feature.
+
+use diffie_hellman_groups::MODPGroup5;
+
+use crate::{
+    Coin, CoinChallenge, DepositLedger, DepositOutcome, Issuer, Params, Spender, SpentCoin,
+};
+
+#[test]
+fn test_lifecycle_without_serde() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let params = Params::try_from(params.to_bytes().as_slice()).unwrap();
+    let issuer = Issuer::new(params.clone());
+    let mut spender = Spender::new(params.clone());
+    spender.set_registration_id(issuer.register(&spender.i).unwrap());
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+    let coin = Coin::from_bytes(&coin.to_bytes()).unwrap();
+    assert!(coin.verify(&issuer.h, &params));
+
+    let mut ledger = DepositLedger::new(params.clone());
+    let mut outcomes = Vec::new();
+    for message in ["shopA", "shopB"] {
+        let challenge = CoinChallenge::new(message.as_bytes(), &coin);
+        let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+        let spent_coin = SpentCoin::from_bytes(&spent_coin.to_bytes()).unwrap();
+        assert!(spent_coin.verify(&challenge, &params));
+        outcomes.push(ledger.deposit(spent_coin, challenge).unwrap());
+    }
+    assert_eq!(outcomes[0], DepositOutcome::Accepted);
+    match &outcomes[1] {
+        DepositOutcome::DoubleSpend(event) => assert_eq!(event.identity, spender.i),
+        _ => panic!("the double spending is not detected"),
+    }
+}
@@ -0,0 +1,63 @@
+//! Defines the signatures of transactions under the one-time keys of coins, which give the
+//! receivers non-repudiable evidence of the transactions in addition to the spent coins.
+
+use num_bigint::BigUint;
+
+use crate::{
+    cryptographics::{hash_to_scalar, schnorr_verify},
+    params::Params,
+};
+
+/// Domain tag of the derivation of the one-time secret key of a coin.
+pub(crate) const ONETIME_KEY_TAG: &[u8] = b"brands-rs/onetime-key";
+
+/// Domain tag of the hash in the signature of a transaction.
+const TRANSACTION_TAG: &[u8] = b"brands-rs/transaction";
+
+/// A signature of a transaction created by the spender under the one-time key of a coin, by
+/// calling the method [sign_transaction](crate::Spender::sign_transaction). The one-time public
+/// key is bound into the coin challenge by [CoinChallenge::new_with_onetime_key](crate::CoinChallenge::new_with_onetime_key),
+/// so that the receiver learns that the signer is the spender of the coin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransactionSignature {
+    pub(crate) e: BigUint,
+    pub(crate) s: BigUint,
+}
+
+impl TransactionSignature {
+    /// Returns true if the signature over the message is created under the one-time public key.
+    pub fn verify(&self, onetime_pk: &BigUint, message: &[u8], params: &Params) -> bool {
+        if !params.is_subgroup_element(onetime_pk) {
+            return false;
+        }
+        schnorr_verify(
+            &self.e,
+            &self.s,
+            onetime_pk,
+            &params.g,
+            &params.p,
+            &params.q,
+            |r| Self::challenge(params, onetime_pk, r, message),
+        )
+    }
+
+    /// Computes the challenge of the signature, e = Hash(pk, r, message) (mod q).
+    pub(crate) fn challenge(
+        params: &Params,
+        onetime_pk: &BigUint,
+        r: &BigUint,
+        message: &[u8],
+    ) -> BigUint {
+        hash_to_scalar(
+            &params.hmac_key(),
+            &[
+                TRANSACTION_TAG,
+                &onetime_pk.to_bytes_le(),
+                &r.to_bytes_le(),
+                message,
+            ],
+            &params.q,
+        )
+    }
+}
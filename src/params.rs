@@ -1,28 +1,81 @@
 //! Define the common parameters used in brands scheme.
 
-use std::str::FromStr;
+use std::{
+    any::type_name,
+    fmt,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+};
 
-use diffie_hellman_groups::{MODPGroup, PrimeGroup};
+use diffie_hellman_groups::MODPGroup;
 use num_bigint::BigUint;
 use rand::Rng;
-use serde::{Deserialize, Serialize};
+
+use crate::{
+    cryptographics::{derive_hmac_key, hash_to_subgroup, random_bits, FixedBaseTable},
+    encoding::{write_biguint_in, write_bytes_in, Endianness, Reader},
+    math::{is_quadratic_residue, is_subgroup_element, is_subgroup_generator},
+    Error, VerifyError,
+};
+
+/// The standard generator of the MODP groups defined in RFC 3526.
+const STANDARD_GENERATOR: u64 = 2;
+
+/// Domain tag for deriving the generators `g1` and `g2` from the standard generator.
+const GENERATOR_TAG: &[u8] = b"brands-rs/generator";
 
 /// Common Parameters used in brands scheme.
-#[derive(Clone, Serialize, Deserialize)]
+///
+/// The big integers are serialized as decimal strings, so that the serialized parameters are
+/// human-readable (e.g. in configuration files).
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Params {
-    /// A customizable string being used in coin withdrawal and verification.
+    /// A customizable string being used in coin withdrawal and verification. It is public, as
+    /// a part of the parameters distributed to the verifiers.
     pub(crate) scheme_key: String,
+    /// The id of the tenant (e.g. a logical mint of a hosting provider) whose hashing domain is
+    /// isolated from the other tenants sharing the same scheme key. Empty if not set.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub(crate) tenant_id: Vec<u8>,
+    /// The byte order of the values in the hash of a coin, and in the encoding by
+    /// [Params::to_bytes]. Little-endian by default.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Endianness::is_little")
+    )]
+    pub(crate) endianness: Endianness,
+    /// A human-readable label of the group (e.g. `MODPGroup5`), which identifies the parameters
+    /// in the logs and error messages. It is not a part of the hash of a coin.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub(crate) label: Option<String>,
 
     /// p = prime
+    #[cfg_attr(feature = "serde", serde(with = "crate::encoding::decimal"))]
     pub(crate) p: BigUint,
     /// q = prime mod n, (i.e. max = n-1), p = 2q + 1, order(p)=n=2q
+    #[cfg_attr(feature = "serde", serde(with = "crate::encoding::decimal"))]
     pub(crate) q: BigUint,
     /// g^q mod p == 1, q != 2
+    #[cfg_attr(feature = "serde", serde(with = "crate::encoding::decimal"))]
     pub(crate) g: BigUint,
     /// g1^q mod p == 1, q != 2
+    #[cfg_attr(feature = "serde", serde(with = "crate::encoding::decimal"))]
     pub(crate) g1: BigUint,
     /// g2^q mod p == 1, q != 2
+    #[cfg_attr(feature = "serde", serde(with = "crate::encoding::decimal"))]
     pub(crate) g2: BigUint,
+
+    /// The table of the powers of g1, which is precomputed on first use and shared by the
+    /// clones of the parameters.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    g1_table: Arc<OnceLock<FixedBaseTable>>,
 }
 
 impl Params {
@@ -30,7 +83,7 @@ impl Params {
     /// parametric values (e.g. "p", "q") cannot be converted into Unsigned big integers.
     ///
     /// This function does not verify whether the inputs are valid (i.e. satisfying the requirements
-    /// of brands scheme).
+    /// of brands scheme). See [Params::from_str_validated] for the validating one.
     ///
     /// ### Example
     /// ```
@@ -53,19 +106,42 @@ impl Params {
     ) -> Option<Self> {
         Some(Self {
             scheme_key,
+            tenant_id: Vec::new(),
+            endianness: Endianness::Little,
             p: BigUint::from_str(p).ok()?,
             q: BigUint::from_str(q).ok()?,
             g: BigUint::from_str(g).ok()?,
             g1: BigUint::from_str(g1).ok()?,
             g2: BigUint::from_str(g2).ok()?,
+            label: None,
+            g1_table: Default::default(),
         })
     }
 
+    /// Instantiates [Params] from string inputs as [Params::from_str] does, and validates them
+    /// by [Params::validate], so that a typo in the inputs does not produce broken parameters.
+    ///
+    /// Returns [Error::DeserializationFailed] if the string inputs cannot be converted into
+    /// unsigned big integers, or the errors of [Params::validate] if the parameters are invalid.
+    pub fn from_str_validated(
+        scheme_key: String,
+        p: &str,
+        q: &str,
+        g: &str,
+        g1: &str,
+        g2: &str,
+    ) -> Result<Self, Error> {
+        let params =
+            Self::from_str(scheme_key, p, q, g, g1, g2).ok_or(Error::DeserializationFailed)?;
+        params.validate()?;
+        Ok(params)
+    }
+
     /// Instantiates [Params] from a [MODPGroup] group which is a Diffie-Hellman group.
     /// The prime modulus `p` and Sophie Germain prime `q` are taken from the group.
     /// The distinct generators `g`, `g1`, and `g2` are generated randomly with bits
-    /// ranging from 2 to the number of bits in the prime modulus `p`.
-    ///
+    /// ranging from 2 to the number of bits in the prime modulus `p`, and resampled unless they
+    /// have the order `q` (see [is_subgroup_generator](crate::math::is_subgroup_generator)).
     ///
     /// ### Example
     /// ```
@@ -78,16 +154,22 @@ impl Params {
         let q = G::sophie_garmain_prime();
 
         let mut rng = rand::thread_rng();
-        let num_bits = rng.gen_range(2..p.bits() as usize);
-        let g = PrimeGroup::new::<G>(num_bits).g;
+        // Resamples the generators which do not have the order q (e.g. 1), see
+        // is_subgroup_generator.
+        let mut generator = || loop {
+            // g = h^2 (mod p) is an element of the subgroup of order q, as p = 2q + 1.
+            let num_bits = rng.gen_range(2..p.bits());
+            let g = random_bits(num_bits, &mut rng).modpow(&BigUint::from(2u64), &p);
+            if is_subgroup_generator(&g, &q, &p) {
+                break g;
+            }
+        };
+        let g = generator();
         let g1;
         let g2;
         loop {
-            let num_bits = rng.gen_range(2..p.bits() as usize);
-            let g1_ = PrimeGroup::new::<G>(num_bits).g;
-
-            let num_bits = rng.gen_range(2..p.bits() as usize);
-            let g2_ = PrimeGroup::new::<G>(num_bits).g;
+            let g1_ = generator();
+            let g2_ = generator();
 
             if g != g1_ && g != g2_ && g1_ != g2_ {
                 g1 = g1_;
@@ -95,20 +177,436 @@ impl Params {
                 break;
             }
         }
-        println!(
-            "g bits: {}, g1 bits: {}, g2 bits: {} ",
-            g.bits(),
-            g1.bits(),
-            g2.bits()
-        );
 
         Self {
             scheme_key,
+            tenant_id: Vec::new(),
+            endianness: Endianness::Little,
             p,
             q,
             g,
             g1,
             g2,
+            label: Some(group_label::<G>()),
+            g1_table: Default::default(),
         }
     }
+
+    /// Instantiates [Params] from a [MODPGroup] group which is a Diffie-Hellman group, with
+    /// the generators `g`, `g1`, and `g2` supplied explicitly. The prime modulus `p` and Sophie
+    /// Germain prime `q` are taken from the group.
+    ///
+    /// Returns [Error::InvalidGenerator] if any of the generators does not have the order `q`
+    /// (e.g. it is 1, or not an element of the subgroup of order `q`), or
+    /// [Error::DuplicateGenerators] if the generators are not distinct.
+    ///
+    /// ### Example
+    /// ```
+    /// use diffie_hellman_groups::MODPGroup5;
+    /// use num_bigint::BigUint;
+    ///
+    /// // Squares (mod p) are elements of the subgroup of order q.
+    /// let params = brands::Params::from_dh_group_with_generators::<MODPGroup5>(
+    ///     "brandskey".to_string(),
+    ///     BigUint::from(4u64),
+    ///     BigUint::from(9u64),
+    ///     BigUint::from(25u64),
+    /// ).unwrap();
+    /// ```
+    pub fn from_dh_group_with_generators<G: MODPGroup>(
+        scheme_key: String,
+        g: BigUint,
+        g1: BigUint,
+        g2: BigUint,
+    ) -> Result<Self, Error> {
+        let params = Self {
+            scheme_key,
+            tenant_id: Vec::new(),
+            endianness: Endianness::Little,
+            p: G::prime_modulus(),
+            q: G::sophie_garmain_prime(),
+            g,
+            g1,
+            g2,
+            label: Some(group_label::<G>()),
+            g1_table: Default::default(),
+        };
+        params.validate_generators()?;
+        Ok(params)
+    }
+
+    /// Instantiates [Params] from a [MODPGroup] group which is a Diffie-Hellman group, taking
+    /// the standard generator of the group (i.e. 2 for the MODP groups of RFC 3526) as `g`. The
+    /// generators `g1` and `g2` are derived from `g` by hashing into the subgroup of order `q`,
+    /// so that the parameters are reproducible and tied to the documented group.
+    ///
+    /// ### Example
+    /// ```
+    /// use diffie_hellman_groups::MODPGroup5;
+    ///
+    /// let params = brands::Params::from_dh_group_canonical::<MODPGroup5>("brandskey".to_string());
+    /// assert!(params.validate().is_ok());
+    /// ```
+    pub fn from_dh_group_canonical<G: MODPGroup>(scheme_key: String) -> Self {
+        let p = G::prime_modulus();
+        // 2 is a quadratic residue modulo the primes of RFC 3526 (i.e. p = 7 mod 8), so it
+        // generates the subgroup of order q.
+        let g = BigUint::from(STANDARD_GENERATOR);
+        let g_bytes = g.to_bytes_le();
+        let g1 = hash_to_subgroup(GENERATOR_TAG, &[&g_bytes, b"g1"], &p);
+        let g2 = hash_to_subgroup(GENERATOR_TAG, &[&g_bytes, b"g2"], &p);
+
+        Self {
+            scheme_key,
+            tenant_id: Vec::new(),
+            endianness: Endianness::Little,
+            q: G::sophie_garmain_prime(),
+            p,
+            g,
+            g1,
+            g2,
+            label: Some(group_label::<G>()),
+            g1_table: Default::default(),
+        }
+    }
+
+    /// Sets the tenant id which is folded into the derivation of the HMac key together with the
+    /// scheme key, so that the coins of different tenants cannot be confused even if they share
+    /// the same scheme key.
+    pub fn with_tenant_id(mut self, tenant_id: &[u8]) -> Self {
+        self.tenant_id = tenant_id.to_vec();
+        self
+    }
+
+    /// Returns the scheme key. The scheme key is a public domain separator of the coin
+    /// challenges rather than a secret, so that anyone holding the parameters (e.g. a
+    /// [CoinVerifier](crate::CoinVerifier) with the identity of the issuer) can verify coins.
+    pub fn scheme_key(&self) -> &str {
+        &self.scheme_key
+    }
+
+    /// Sets the byte order of the values in the hash of a coin, e.g. for interoperating with an
+    /// implementation using big-endian. The coins are verified with the same byte order as
+    /// they are withdrawn, so the spenders and verifiers of an issuer must use the same one.
+    pub fn with_endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
+    }
+
+    /// Sets the human-readable label of the group, which replaces the one set by the constructor.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Returns the human-readable label of the group. The constructors from a [MODPGroup] set it
+    /// to the name of the group, while [Params::from_str] and the decoding from the bytes of
+    /// [Params::to_bytes] leave it unset, as the label is not encoded in the bytes.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Returns the error of the coin failing verification for the reason, which names the group
+    /// if the parameters are labelled.
+    pub(crate) fn invalid_coin(&self, reason: VerifyError) -> Error {
+        match &self.label {
+            Some(label) => Error::InvalidCoinInGroup {
+                label: label.clone(),
+                reason,
+            },
+            None => Error::InvalidCoin(reason),
+        }
+    }
+
+    /// Returns the byte order of the values in the hash of a coin.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Returns the tenant id, which is empty if not set.
+    pub fn tenant_id(&self) -> &[u8] {
+        &self.tenant_id
+    }
+
+    /// Returns true if `x` is an element of the subgroup of order `q` (mod p), i.e. `0 < x < p`
+    /// and `x^q = 1 (mod p)`.
+    ///
+    /// If `p = 2q + 1` (e.g. the MODP groups), the check is done by
+    /// [Params::is_quadratic_residue] without a modular exponentiation.
+    pub fn is_subgroup_element(&self, x: &BigUint) -> bool {
+        if self.p == &self.q * 2u64 + 1u64 {
+            self.is_quadratic_residue(x)
+        } else {
+            is_subgroup_element(x, &self.q, &self.p)
+        }
+    }
+
+    /// Returns true if `x` is a quadratic residue modulo `p` other than 0, by computing the
+    /// Jacobi symbol `(x / p)` (Euler's criterion without the exponentiation `x^((p-1)/2)`). For
+    /// the safe prime `p = 2q + 1`, it is equivalent to [Params::is_subgroup_element] but several
+    /// times faster.
+    pub fn is_quadratic_residue(&self, x: &BigUint) -> bool {
+        is_quadratic_residue(x, &self.p)
+    }
+
+    /// Returns the length in bytes of a scalar (i.e. a value modulo `q`) in the padded encodings,
+    /// e.g. [Coin::to_padded_bytes](crate::Coin::to_padded_bytes).
+    pub fn serialized_scalar_len(&self) -> usize {
+        self.q.bits().div_ceil(8) as usize
+    }
+
+    /// Returns the length in bytes of a group element (i.e. a value modulo `p`) in the padded
+    /// encodings, e.g. [Coin::to_padded_bytes](crate::Coin::to_padded_bytes).
+    pub fn serialized_element_len(&self) -> usize {
+        self.p.bits().div_ceil(8) as usize
+    }
+
+    /// Encodes the parameters into bytes, which are a byte of the endianness (0 for little-endian
+    /// and 1 for big-endian), followed by the length-prefixed scheme key, tenant id, `p`, `q`,
+    /// `g`, `g1` and `g2` in order, encoded in that byte order. The parameters can be decoded by
+    /// `Params::try_from`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let endianness = self.endianness;
+        let mut buf = vec![match endianness {
+            Endianness::Little => 0,
+            Endianness::Big => 1,
+        }];
+        write_bytes_in(&mut buf, self.scheme_key.as_bytes(), endianness);
+        write_bytes_in(&mut buf, &self.tenant_id, endianness);
+        for x in [&self.p, &self.q, &self.g, &self.g1, &self.g2] {
+            write_biguint_in(&mut buf, x, 0, endianness);
+        }
+        buf
+    }
+
+    /// Validates the parameters, i.e. `p = 2q + 1`, and the generators are distinct elements of
+    /// the subgroup of order `q` other than 1. The primality of `p` and `q` is not tested.
+    ///
+    /// Returns [Error::InvalidParams] if `p` is not `2q + 1`, or the errors of
+    /// [from_dh_group_with_generators](crate::Params::from_dh_group_with_generators) if the
+    /// generators are invalid.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.p != &self.q * 2u64 + 1u64 {
+            return Err(Error::InvalidParams);
+        }
+        self.validate_generators()
+    }
+
+    /// Returns a conservative estimate of the bits of security of the parameters, i.e. the
+    /// lesser of the strengths against the discrete logarithm in the subgroup of order `q` and
+    /// in the group of `p`, as a sanity check of the parameters loaded from an untrusted source.
+    ///
+    /// The heuristic is:
+    /// - the generic attacks (e.g. Pollard's rho) in the subgroup take about `sqrt(q)` steps,
+    ///   i.e. `bits(q) / 2` bits;
+    /// - the number field sieve on `p` is estimated by the strengths of NIST SP 800-57 for the
+    ///   finite field groups (i.e. 80, 112, 128, 192 and 256 bits for `p` of 1024, 2048, 3072,
+    ///   7680 and 15360 bits), rounded down to the nearest step. Below 1024 bits, it is estimated
+    ///   by the asymptotic complexity of the number field sieve, capped below 80 bits.
+    ///
+    /// E.g. the 1536-bit MODP group yields 80 bits.
+    pub fn security_bits(&self) -> usize {
+        let subgroup_bits = self.q.bits() as usize / 2;
+        subgroup_bits.min(nfs_security_bits(self.p.bits() as usize))
+    }
+
+    /// Checks that the generators are distinct and have the order `q`.
+    fn validate_generators(&self) -> Result<(), Error> {
+        for x in [&self.g, &self.g1, &self.g2] {
+            if !is_subgroup_generator(x, &self.q, &self.p) {
+                return Err(Error::InvalidGenerator);
+            }
+        }
+        if self.g == self.g1 || self.g == self.g2 || self.g1 == self.g2 {
+            return Err(Error::DuplicateGenerators);
+        }
+        Ok(())
+    }
+
+    /// Returns `g1^e (mod p)` by the table of the powers of g1, which makes the repeated
+    /// exponentiations (e.g. revealing identities in bulk) faster. The table takes about
+    /// `16 * bits(q) / 4` group elements of memory and is precomputed on first call.
+    pub(crate) fn g1_pow(&self, e: &BigUint) -> BigUint {
+        self.g1_table
+            .get_or_init(|| FixedBaseTable::new(&self.g1, self.q.bits(), &self.p))
+            .pow(e)
+    }
+
+    /// Returns the HMac key derived from the scheme key, which is used in coin withdrawal and
+    /// verification.
+    pub(crate) fn hmac_key(&self) -> [u8; 32] {
+        derive_hmac_key(self.scheme_key.as_bytes(), &self.tenant_id)
+    }
+}
+
+impl fmt::Debug for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Params")
+            .field("label", &self.label)
+            .field("scheme_key", &self.scheme_key)
+            .field("tenant_id", &self.tenant_id)
+            .field("endianness", &self.endianness)
+            .field("p_bits", &self.p.bits())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The strengths (in bits) of the finite field groups by the bits of the prime modulus, in
+/// NIST SP 800-57 Part 1 Table 2.
+const NFS_SECURITY_BITS: [(usize, usize); 5] = [
+    (15360, 256),
+    (7680, 192),
+    (3072, 128),
+    (2048, 112),
+    (1024, 80),
+];
+
+/// Estimates the bits of security against the number field sieve on the prime modulus of the
+/// bits, see [Params::security_bits].
+fn nfs_security_bits(p_bits: usize) -> usize {
+    if let Some((_, bits)) = NFS_SECURITY_BITS.iter().find(|(min, _)| p_bits >= *min) {
+        return *bits;
+    }
+    // L_p[1/3, (64/9)^(1/3)] = exp(1.923 * (ln p)^(1/3) * (ln ln p)^(2/3))
+    let ln_p = (p_bits as f64) * std::f64::consts::LN_2;
+    let work = 1.923 * ln_p.cbrt() * ln_p.ln().max(0.0).powf(2.0 / 3.0);
+    ((work / std::f64::consts::LN_2) as usize).min(79)
+}
+
+/// Returns the name of the [MODPGroup] group (e.g. `MODPGroup5`) as the label of the parameters.
+fn group_label<G: MODPGroup>() -> String {
+    let name = type_name::<G>();
+    name.rsplit("::").next().unwrap_or(name).to_string()
+}
+
+/// The policy of generating the generators `g`, `g1`, and `g2` by [ParamsBuilder].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum GeneratorPolicy {
+    /// As [Params::from_dh_group] does.
+    #[default]
+    Random,
+    /// As [Params::from_dh_group_canonical] does.
+    Deterministic,
+}
+
+/// Instantiates [Params] from a [MODPGroup] group by the generator policy.
+fn params_from_group<G: MODPGroup>(scheme_key: String, policy: GeneratorPolicy) -> Params {
+    match policy {
+        GeneratorPolicy::Random => Params::from_dh_group::<G>(scheme_key),
+        GeneratorPolicy::Deterministic => Params::from_dh_group_canonical::<G>(scheme_key),
+    }
+}
+
+/// A builder of [Params], as an alternative to the constructors of [Params]. The scheme key and
+/// the group are required, and the generators are random by default.
+///
+/// ### Example
+/// ```
+/// use brands::ParamsBuilder;
+/// use diffie_hellman_groups::MODPGroup5;
+///
+/// let params = ParamsBuilder::new()
+///     .scheme_key("brandskey")
+///     .group::<MODPGroup5>()
+///     .deterministic_generators()
+///     .validate()
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Default)]
+pub struct ParamsBuilder {
+    scheme_key: Option<String>,
+    group: Option<fn(String, GeneratorPolicy) -> Params>,
+    policy: GeneratorPolicy,
+    validate: bool,
+}
+
+impl ParamsBuilder {
+    /// Creates a builder without any setting.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the scheme key, see [Params::scheme_key].
+    pub fn scheme_key(mut self, scheme_key: impl Into<String>) -> Self {
+        self.scheme_key = Some(scheme_key.into());
+        self
+    }
+
+    /// Sets the [MODPGroup] group which is a Diffie-Hellman group, from which the prime
+    /// modulus `p` and Sophie Germain prime `q` are taken.
+    pub fn group<G: MODPGroup>(mut self) -> Self {
+        self.group = Some(params_from_group::<G>);
+        self
+    }
+
+    /// Derives the generators from the standard generator of the group, as
+    /// [Params::from_dh_group_canonical] does.
+    pub fn deterministic_generators(mut self) -> Self {
+        self.policy = GeneratorPolicy::Deterministic;
+        self
+    }
+
+    /// Generates the generators randomly, as [Params::from_dh_group] does. This is the default.
+    pub fn random_generators(mut self) -> Self {
+        self.policy = GeneratorPolicy::Random;
+        self
+    }
+
+    /// Validates the parameters by [Params::validate] when building them.
+    pub fn validate(mut self) -> Self {
+        self.validate = true;
+        self
+    }
+
+    /// Builds the parameters.
+    ///
+    /// Returns [Error::MissingSetting] if the scheme key or the group is not set, or the error
+    /// of [Params::validate] if the validation is requested and fails.
+    pub fn build(self) -> Result<Params, Error> {
+        let (Some(scheme_key), Some(group)) = (self.scheme_key, self.group) else {
+            return Err(Error::MissingSetting);
+        };
+        let params = group(scheme_key, self.policy);
+        if self.validate {
+            params.validate()?;
+        }
+        Ok(params)
+    }
+}
+
+impl TryFrom<&[u8]> for Params {
+    type Error = Error;
+
+    /// Decodes the parameters from the bytes encoded by [Params::to_bytes], and validates them
+    /// by [Params::validate].
+    ///
+    /// Returns [Error::DeserializationFailed] if the bytes are malformed, or the errors of
+    /// [Params::validate] if the parameters are invalid.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let (endianness, bytes) = match bytes.split_first() {
+            Some((0, bytes)) => (Endianness::Little, bytes),
+            Some((1, bytes)) => (Endianness::Big, bytes),
+            _ => return Err(Error::DeserializationFailed),
+        };
+        let mut reader = Reader::with_endianness(bytes, endianness);
+        let scheme_key = String::from_utf8(reader.read_bytes()?.to_vec())
+            .map_err(|_| Error::DeserializationFailed)?;
+        let tenant_id = reader.read_bytes()?.to_vec();
+        let params = Self {
+            scheme_key,
+            tenant_id,
+            endianness,
+            p: reader.read_biguint()?,
+            q: reader.read_biguint()?,
+            g: reader.read_biguint()?,
+            g1: reader.read_biguint()?,
+            g2: reader.read_biguint()?,
+            label: None,
+            g1_table: Default::default(),
+        };
+        reader.finish()?;
+        params.validate()?;
+        Ok(params)
+    }
 }
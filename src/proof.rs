@@ -0,0 +1,147 @@
+//! Defines the compact proof of validity of a coin, for the receivers with constrained bandwidth.
+
+use num_bigint::BigUint;
+
+use crate::{
+    coin::{check_issuer_key, coin_challenge_d, CoinMetadata, K_SHOW_MARKER},
+    cryptographics::ct_eq_biguint,
+    encoding::{write_biguint_in, Endianness, Reader},
+    math::modinv_checked,
+    Error, Identity, Params, VerifyError,
+};
+
+/// A proof of validity of a [Coin](crate::Coin), created by the method
+/// [proof](crate::Coin::proof). It omits the fields `c4` and `c5` of the coin, which are
+/// recomputed from the verification equations `c4 = g^c6 / h^cd` and `c5 = c1^c6 / c3^cd`, and
+/// then checked against the challenge `cd`. The proof therefore does not cover the `c4` and `c5`
+/// carried by the coin.
+///
+/// The proof is two group elements smaller than the coin, e.g. about 980 bytes instead of about
+/// 1370 bytes (i.e. 28% smaller) encoded by [CoinProof::to_bytes] and
+/// [Coin::to_bytes](crate::Coin::to_bytes) in the 1536-bit MODP group.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoinProof {
+    pub(crate) c1: BigUint,
+    pub(crate) c2: BigUint,
+    pub(crate) c3: BigUint,
+    pub(crate) c6: BigUint,
+    pub(crate) cd: BigUint,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) metadata: Option<CoinMetadata>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub(crate) c2_extra: Vec<BigUint>,
+}
+
+impl CoinProof {
+    /// Returns true if the coin of this proof is issued by the issuer whose identity is `h`, as
+    /// [Coin::verify](crate::Coin::verify) does for the coin.
+    pub fn verify(&self, h: &Identity, params: &Params) -> bool {
+        self.check(h, params).is_ok()
+    }
+
+    /// Verifies the proof as [CoinProof::verify] does, but returns the reason of the failure.
+    fn check(&self, h: &Identity, params: &Params) -> Result<(), VerifyError> {
+        let one = BigUint::from(1u64);
+        let c2: Vec<&BigUint> = std::iter::once(&self.c2).chain(&self.c2_extra).collect();
+        if self.c1 == one || self.c3 == one || c2.iter().any(|c2| **c2 == one) {
+            return Err(VerifyError::DegenerateCoin);
+        }
+        if self.cd >= params.q {
+            return Err(VerifyError::LegacyFormat);
+        }
+        check_issuer_key(h, params)?;
+
+        let p = &params.p;
+        // x / y^cd (mod p)
+        let div_pow = |x: BigUint, y: &BigUint| {
+            modinv_checked(&y.modpow(&self.cd, p), p)
+                .map(|inv| (x * inv) % p)
+                .map_err(|_| VerifyError::SignatureEqnFailed)
+        };
+        // c4 = g^c6 / h^cd
+        let c4 = div_pow(params.g.modpow(&self.c6, p), h)?;
+        // c5 = c1^c6 / c3^cd
+        let c5 = div_pow(self.c1.modpow(&self.c6, p), &self.c3)?;
+
+        let ver_cd = coin_challenge_d(
+            params,
+            &self.c1,
+            &c2,
+            &self.c3,
+            &c4,
+            &c5,
+            self.metadata.as_ref(),
+        );
+        let width = p.bits().div_ceil(8) as usize;
+        if !bool::from(ct_eq_biguint(&self.cd, &ver_cd, width)) {
+            return Err(VerifyError::ChallengeMismatch);
+        }
+        Ok(())
+    }
+
+    /// Encodes the proof into bytes, in the same layout as [Coin::to_bytes](crate::Coin::to_bytes)
+    /// without the fields `c4` and `c5`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with_endianness(Endianness::Little)
+    }
+
+    /// Encodes the proof as [CoinProof::to_bytes] does, in the byte order `endianness`. The
+    /// proof can be decoded by [CoinProof::from_bytes_with_endianness] with the same byte order.
+    pub fn to_bytes_with_endianness(&self, endianness: Endianness) -> Vec<u8> {
+        let mut buf = Vec::new();
+        if !self.c2_extra.is_empty() {
+            buf.extend_from_slice(&endianness.u32_to_bytes(K_SHOW_MARKER));
+            buf.extend_from_slice(&endianness.u32_to_bytes(self.c2_extra.len() as u32));
+            for x in &self.c2_extra {
+                write_biguint_in(&mut buf, x, 0, endianness);
+            }
+        }
+        for x in [&self.c1, &self.c2, &self.c3, &self.c6, &self.cd] {
+            write_biguint_in(&mut buf, x, 0, endianness);
+        }
+        if let Some(metadata) = &self.metadata {
+            buf.extend_from_slice(&metadata.to_bytes(endianness));
+        }
+        buf
+    }
+
+    /// Decodes a proof from the bytes encoded by [CoinProof::to_bytes]. Returns
+    /// [Error::DeserializationFailed] if the bytes are malformed, or a field is declared longer
+    /// than 4096 bytes.
+    ///
+    /// The proof is not verified. Call [CoinProof::verify] before accepting it.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes_with_endianness(bytes, Endianness::Little)
+    }
+
+    /// Decodes a proof from the bytes encoded by [CoinProof::to_bytes_with_endianness] in the
+    /// byte order `endianness`, as [CoinProof::from_bytes] does.
+    pub fn from_bytes_with_endianness(bytes: &[u8], endianness: Endianness) -> Result<Self, Error> {
+        let mut reader = Reader::with_endianness(bytes, endianness);
+        let mut c2_extra = Vec::new();
+        if bytes.starts_with(&endianness.u32_to_bytes(K_SHOW_MARKER)) {
+            reader.read_u32()?;
+            for _ in 0..reader.read_u32()? {
+                c2_extra.push(reader.read_biguint()?);
+            }
+        }
+        let mut proof = Self {
+            c1: reader.read_biguint()?,
+            c2: reader.read_biguint()?,
+            c3: reader.read_biguint()?,
+            c6: reader.read_biguint()?,
+            cd: reader.read_biguint()?,
+            metadata: None,
+            c2_extra,
+        };
+        if !reader.is_empty() {
+            proof.metadata = Some(CoinMetadata::read(&mut reader)?);
+        }
+        reader.finish()?;
+        Ok(proof)
+    }
+}
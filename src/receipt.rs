@@ -0,0 +1,61 @@
+//! Defines the receipt issued by an issuer upon a deposit of a spent coin.
+
+use num_bigint::BigUint;
+
+use crate::{
+    coin::CoinFingerprint,
+    cryptographics::{hash_to_scalar, schnorr_verify},
+    params::Params,
+    Identity,
+};
+
+/// Domain tag of the hash in the signature of a receipt.
+const RECEIPT_TAG: &[u8] = b"brands-rs/receipt";
+
+/// A receipt issued by the issuer to the receiver who deposits a spent coin, by calling the
+/// method [issue_receipt](crate::Issuer::issue_receipt). It is a Schnorr signature of the issuer
+/// over the fingerprint of the coin and the amount credited, which the receiver can keep as
+/// a proof of credit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Receipt {
+    /// The fingerprint of the deposited coin.
+    pub fingerprint: CoinFingerprint,
+    /// The amount credited to the receiver.
+    pub amount: u64,
+    pub(crate) e: BigUint,
+    pub(crate) s: BigUint,
+}
+
+impl Receipt {
+    /// Returns true if the receipt is signed by the issuer whose identity is `h`.
+    pub fn verify(&self, h: &Identity, params: &Params) -> bool {
+        if !params.is_subgroup_element(h) {
+            return false;
+        }
+        schnorr_verify(&self.e, &self.s, h, &params.g, &params.p, &params.q, |r| {
+            Self::challenge(params, h, r, &self.fingerprint, self.amount)
+        })
+    }
+
+    /// Computes the challenge of the signature, e = Hash(h, r, fingerprint, amount) (mod q).
+    pub(crate) fn challenge(
+        params: &Params,
+        h: &Identity,
+        r: &BigUint,
+        fingerprint: &CoinFingerprint,
+        amount: u64,
+    ) -> BigUint {
+        hash_to_scalar(
+            &params.hmac_key(),
+            &[
+                RECEIPT_TAG.to_vec(),
+                h.to_bytes_le(),
+                r.to_bytes_le(),
+                fingerprint.0.to_vec(),
+                amount.to_le_bytes().to_vec(),
+            ],
+            &params.q,
+        )
+    }
+}
@@ -0,0 +1,41 @@
+//! Implements of the protocol steps involved by a coin receiver (e.g. a merchant) in the
+//! interactive spending of a coin.
+
+use rand::RngCore;
+
+use crate::{
+    coin::{Coin, CoinChallenge},
+    cryptographics::hash_to_number,
+};
+
+/// A coin receiver in the interactive spending of a coin, which challenges the spender with
+/// fresh randomness instead of a message.
+///
+/// A challenge created by [CoinChallenge::new] is derived from the message (e.g. the payment
+/// details) by Fiat-Shamir, so the spender who knows the message in advance can compute the
+/// responses before the payment. A challenge created by [Receiver::challenge] is unpredictable
+/// to the spender, who must respond after receiving it, while it does not commit to the
+/// payment. The spent coin is computed by [Spender::spend](crate::Spender::spend) and verified
+/// by [SpentCoin::verify](crate::SpentCoin::verify) in the same way for both kinds of
+/// challenges.
+pub struct Receiver;
+
+impl Receiver {
+    /// Creates a random challenge for the coin offered by the spender. The challenge binds the
+    /// coin as [CoinChallenge::new] does, with a random 256-bit value in place of the message,
+    /// so it must be created after the coin is received.
+    pub fn challenge(coin: &Coin) -> CoinChallenge {
+        Self::challenge_with_rng(coin, &mut rand::thread_rng())
+    }
+
+    /// Creates a random challenge as [Receiver::challenge] does, with the random value sampled
+    /// from `rng` (e.g. `&mut dyn RngCore` supplied by a WASM host).
+    pub fn challenge_with_rng<R: RngCore + ?Sized>(coin: &Coin, rng: &mut R) -> CoinChallenge {
+        let mut nonce = [0u8; 32];
+        rng.fill_bytes(&mut nonce);
+        CoinChallenge(hash_to_number(
+            &nonce,
+            &[coin.c1.to_bytes_le(), coin.c2.to_bytes_le()],
+        ))
+    }
+}
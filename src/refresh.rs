@@ -0,0 +1,42 @@
+//! Defines the request of refreshing a coin, i.e. exchanging an unspent coin for a new one.
+
+use crate::{
+    coin::{Coin, CoinChallenge, SpentCoin},
+    withdrawal::WithdrawalChallenge,
+};
+
+/// Domain tag of the coin challenges upon which the old coins are spent in refreshing.
+const REFRESH_TAG: &[u8] = b"brands-rs/refresh";
+
+/// A request created by spender to issuer for refreshing a coin, by calling the method
+/// [request_refresh](crate::Spender::request_refresh). It contains the old coin spent upon a
+/// challenge bound to the withdrawal of the new coin, which proves the ownership of the old coin.
+///
+/// The request is handled by the issuer by calling the method [refresh](crate::Issuer::refresh).
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RefreshRequest {
+    /// The old coin spent upon the challenge returned by [RefreshRequest::challenge].
+    pub spent_coin: SpentCoin,
+    /// The challenge of the withdrawal of the new coin.
+    pub withdrawal_challenge: WithdrawalChallenge,
+}
+
+impl RefreshRequest {
+    /// Returns the coin challenge upon which the old coin is spent, which is bound to the
+    /// withdrawal challenge of the new coin, so that the spent coin cannot be reused for
+    /// another withdrawal.
+    pub fn challenge(&self) -> CoinChallenge {
+        refresh_challenge(&self.spent_coin.coin, &self.withdrawal_challenge)
+    }
+}
+
+/// Computes the coin challenge of the old coin bound to the withdrawal challenge of the new coin.
+pub(crate) fn refresh_challenge(
+    coin: &Coin,
+    withdrawal_challenge: &WithdrawalChallenge,
+) -> CoinChallenge {
+    let mut message = REFRESH_TAG.to_vec();
+    message.extend_from_slice(&withdrawal_challenge.c.to_bytes_le());
+    CoinChallenge::new(&message, coin)
+}
@@ -0,0 +1,34 @@
+//! Defines the anonymized record of a coin, for publishing datasets of coins for research.
+
+/// An anonymized record of a [Coin](crate::Coin), created by the method
+/// [to_research_record](crate::Coin::to_research_record). It contains the bit lengths of the
+/// fields of the coin and its fingerprint, but none of the values of the fields, so that a
+/// dataset of the records can be published without leaking the contents of the coins.
+///
+/// The fields are flat (i.e. no nested values), so that the records can be serialized into a
+/// row of CSV as well as JSON.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResearchRecord {
+    /// The fingerprint of the coin in lowercase hex, see
+    /// [Coin::fingerprint](crate::Coin::fingerprint).
+    pub fingerprint: String,
+    /// The bit length of `c1`.
+    pub c1_bits: u64,
+    /// The bit length of `c2`.
+    pub c2_bits: u64,
+    /// The bit length of `c3`.
+    pub c3_bits: u64,
+    /// The bit length of `c4`.
+    pub c4_bits: u64,
+    /// The bit length of `c5`.
+    pub c5_bits: u64,
+    /// The bit length of `c6`.
+    pub c6_bits: u64,
+    /// The bit length of `cd`.
+    pub cd_bits: u64,
+    /// The number of shows of the coin.
+    pub shows: u32,
+    /// Whether the coin carries metadata.
+    pub has_metadata: bool,
+}
@@ -0,0 +1,86 @@
+//! Defines the session of the coin withdrawal process on the spender side, which enforces the
+//! order of the protocol steps at compile time.
+
+use crate::{
+    coin::CoinWithSecrets, spender::Spender, withdrawal::WithdrawalChallenge, Error, Withdrawal,
+    WithdrawalParams, WithdrawalResponse,
+};
+
+/// The state of a [WithdrawalSession] which has received the withdrawal parameters from the
+/// issuer, and is ready to create the challenge.
+pub struct WithdrawalReady {
+    params: WithdrawalParams,
+}
+
+/// The state of a [WithdrawalSession] which has created the challenge, and is waiting for the
+/// response of the issuer.
+pub struct WithdrawalChallenged {
+    withdrawal: Withdrawal,
+}
+
+/// A coin withdrawal process of a spender, as an alternative to calling the methods
+/// [withdraw](crate::Spender::withdraw) and [make_coin_with_secrets](crate::Spender::make_coin_with_secrets)
+/// in order. The methods of the session are available only in the corresponding state, so that
+/// calling them out of order does not compile.
+///
+/// ```
+/// use brands::{Issuer, Params, Spender, WithdrawalSession};
+/// use diffie_hellman_groups::MODPGroup5;
+///
+/// let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+/// let issuer = Issuer::new(params.clone());
+/// let mut spender = Spender::new(params.clone());
+/// spender.set_registration_id(issuer.register(&spender.i).unwrap());
+///
+/// let (withdrawal_params, withdrawal_response_params) = issuer.setup_withdrawal_params(&spender.i);
+/// let session = WithdrawalSession::new(&spender, withdrawal_params);
+/// let (session, withdrawal_challenge) = session.challenge().unwrap();
+/// let withdrawal_response = issuer
+///     .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+///     .unwrap();
+/// let coin_with_secrets = session.finalize(withdrawal_response);
+/// assert!(coin_with_secrets.coin.verify(&issuer.h, &params));
+/// ```
+pub struct WithdrawalSession<'a, S> {
+    spender: &'a Spender,
+    state: S,
+}
+
+impl<'a> WithdrawalSession<'a, WithdrawalReady> {
+    /// Starts the session with the withdrawal parameters given by the issuer.
+    pub fn new(spender: &'a Spender, params: WithdrawalParams) -> Self {
+        Self {
+            spender,
+            state: WithdrawalReady { params },
+        }
+    }
+
+    /// Creates the challenge to the issuer, as [withdraw](crate::Spender::withdraw) does.
+    pub fn challenge(
+        self,
+    ) -> Result<
+        (
+            WithdrawalSession<'a, WithdrawalChallenged>,
+            WithdrawalChallenge,
+        ),
+        Error,
+    > {
+        let (withdrawal, challenge) = self.spender.withdraw(self.state.params)?;
+        Ok((
+            WithdrawalSession {
+                spender: self.spender,
+                state: WithdrawalChallenged { withdrawal },
+            },
+            challenge,
+        ))
+    }
+}
+
+impl WithdrawalSession<'_, WithdrawalChallenged> {
+    /// Makes the coin by the response of the issuer, as
+    /// [make_coin_with_secrets](crate::Spender::make_coin_with_secrets) does. The session ends.
+    pub fn finalize(self, response: WithdrawalResponse) -> CoinWithSecrets {
+        self.spender
+            .make_coin_with_secrets(self.state.withdrawal, response)
+    }
+}
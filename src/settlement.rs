@@ -0,0 +1,97 @@
+//! Defines the settlement of the spent coins deposited to an issuer, which bundles the
+//! verification, the deposit into the ledger and the receipt in one step.
+
+use rand::RngCore;
+
+use crate::{
+    coin::{CoinChallenge, SpentCoin},
+    issuer::Issuer,
+    ledger::{DepositLedger, DepositOutcome, DoubleSpendEvent},
+    receipt::Receipt,
+    verifier::CoinVerifier,
+    Error,
+};
+
+/// The result of settling a spent coin by [Settlement::settle].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettlementResult {
+    /// The coin is deposited for the first time, and the receiver is credited with the receipt.
+    Accepted(Receipt),
+    /// The same spent coin (i.e. same coin and responses) has been settled before.
+    Duplicate,
+    /// The coin has been spent before upon another challenge. The identity of the double
+    /// spender is revealed in the event.
+    DoubleSpend(DoubleSpendEvent),
+}
+
+/// The deposit flow of an issuer, which verifies the spent coins, deposits them into a
+/// [DepositLedger] and issues the receipts of the accepted ones, so that a service does not
+/// need to wire the [CoinVerifier], the ledger and [Issuer::issue_receipt] by itself.
+pub struct Settlement {
+    issuer: Issuer,
+    verifier: CoinVerifier,
+    ledger: DepositLedger,
+}
+
+impl Settlement {
+    /// Instantiates the settlement of the issuer, with an empty ledger.
+    pub fn new(issuer: Issuer) -> Self {
+        let verifier = CoinVerifier::new(issuer.h.clone(), issuer.params.clone());
+        let ledger = DepositLedger::new(issuer.params.clone());
+        Self {
+            issuer,
+            verifier,
+            ledger,
+        }
+    }
+
+    /// Settles the spent coin with the coin challenge upon which it was spent. The spent coin
+    /// is verified, and then deposited into the ledger. The receipt is issued only if the coin
+    /// is deposited for the first time.
+    ///
+    /// Returns [Error::InvalidCoin] (or [Error::InvalidCoinInGroup] if the parameters are
+    /// labelled) with the reason if the spent coin is not issued by the issuer or not valid upon
+    /// the coin challenge, or the errors of [DepositLedger::deposit].
+    pub fn settle(
+        &mut self,
+        spent_coin: SpentCoin,
+        challenge: CoinChallenge,
+    ) -> Result<SettlementResult, Error> {
+        self.settle_with_rng(spent_coin, challenge, &mut rand::thread_rng())
+    }
+
+    /// Settles the spent coin as [Settlement::settle] does, with the nonce of the signature of
+    /// the receipt sampled from `rng`.
+    pub fn settle_with_rng<R: RngCore + ?Sized>(
+        &mut self,
+        spent_coin: SpentCoin,
+        challenge: CoinChallenge,
+        rng: &mut R,
+    ) -> Result<SettlementResult, Error> {
+        self.verifier
+            .verify_spent(&spent_coin, &challenge)
+            .map_err(|reason| self.verifier.params.invalid_coin(reason))?;
+        Ok(match self.ledger.deposit(spent_coin.clone(), challenge)? {
+            DepositOutcome::Accepted => {
+                SettlementResult::Accepted(self.issuer.sign_receipt(&spent_coin, rng))
+            }
+            DepositOutcome::Duplicate => SettlementResult::Duplicate,
+            DepositOutcome::DoubleSpend(event) => SettlementResult::DoubleSpend(event),
+        })
+    }
+
+    /// Returns the issuer of the settlement.
+    pub fn issuer(&self) -> &Issuer {
+        &self.issuer
+    }
+
+    /// Returns the verifier of the spent coins, which carries the public data of the issuer.
+    pub fn verifier(&self) -> &CoinVerifier {
+        &self.verifier
+    }
+
+    /// Returns the ledger of the deposited spent coins.
+    pub fn ledger(&self) -> &DepositLedger {
+        &self.ledger
+    }
+}
@@ -1,13 +1,23 @@
 //! Implements of the protocol steps involved by a Spender in the scheme.
 
+use std::collections::HashSet;
+
+use hkdf::Hkdf;
 use num_bigint::BigUint;
+use rand::RngCore;
+use sha2::Sha256;
 
 use crate::{
-    coin::{Coin, CoinChallenge, PartialCoin, SpentCoin},
-    cryptographics::{hash_to_number, random_number},
+    coin::{
+        coin_challenge_d, Coin, CoinChallenge, CoinFingerprint, CoinMetadata, CoinWithSecrets,
+        PartialCoin, SpentCoin,
+    },
+    cryptographics::{hash_to_scalar, hkdf_to_scalar, random_nonzero, schnorr_sign},
+    onetime::{TransactionSignature, ONETIME_KEY_TAG},
     params::Params,
+    refresh::{refresh_challenge, RefreshRequest},
     withdrawal::{Withdrawal, WithdrawalChallenge, WithdrawalResponse},
-    Identity, RegistrationID, WithdrawalParams,
+    Error, Identity, RegistrationID, WithdrawalParams,
 };
 
 /// A mathematic representation of a spender in the scheme, which implements
@@ -22,11 +32,26 @@ pub struct Spender {
     /// The value given by the issuer for proving an issued coin.
     /// None if the spender has not yet complete registration with issuer.
     z: Option<RegistrationID>,
+    /// Fingerprints of the coins spent by [spend_once](crate::Spender::spend_once).
+    spent: HashSet<CoinFingerprint>,
+    /// The HKDF pseudorandom key extracted from the seed, if the spender is derived from a seed
+    /// by [new_deterministic](crate::Spender::new_deterministic).
+    hkdf: Option<Hkdf<Sha256>>,
 }
 
+/// Domain tag for deriving the secret values of a spender from a seed.
+const SEED_SALT: &[u8] = b"brands-rs/spender-seed";
+
 impl Spender {
     pub fn new(params: Params) -> Self {
-        let u1 = random_number(&params.q);
+        Self::new_with_rng(params, &mut rand::thread_rng())
+    }
+
+    /// Instantiates a spender as [Spender::new] does, with the secret value sampled from `rng`
+    /// (e.g. `&mut dyn RngCore` supplied by a WASM host where `rand::thread_rng` is not
+    /// available).
+    pub fn new_with_rng<R: RngCore + ?Sized>(params: Params, rng: &mut R) -> Self {
+        let u1 = random_nonzero(&params.q, rng);
         // i = g1^u1 mod p
         let i = params.g1.modpow(&u1, &params.p);
         Self {
@@ -34,7 +59,56 @@ impl Spender {
             i,
             u1,
             z: None,
+            spent: HashSet::new(),
+            hkdf: None,
+        }
+    }
+
+    /// Instantiates a spender whose secret values are all derived from the `seed` (e.g. the
+    /// seed of a wallet) by HKDF-Sha256, so that the spender and the coins withdrawn by the method
+    /// [withdraw_deterministic](crate::Spender::withdraw_deterministic) can be recovered from the
+    /// same seed.
+    ///
+    /// The registration ID is not derived from the seed, but can be restored by calling
+    /// [set_registration_id()](crate::Spender::set_registration_id) again.
+    pub fn new_deterministic(params: Params, seed: &[u8]) -> Self {
+        let hkdf = Hkdf::<Sha256>::new(Some(SEED_SALT), seed);
+        let u1 = hkdf_to_scalar(&hkdf, &[b"u1"], &params.q);
+        // i = g1^u1 mod p
+        let i = params.g1.modpow(&u1, &params.p);
+        Self {
+            params,
+            i,
+            u1,
+            z: None,
+            spent: HashSet::new(),
+            hkdf: Some(hkdf),
+        }
+    }
+
+    /// Restores a spender from the persisted secret value `u1` and the registration ID given by
+    /// the issuer (if the spender has registered), e.g. after the spender process restarts. The
+    /// identity of the spender is recomputed from `u1`.
+    ///
+    /// Returns [Error::InvalidSecret] if `u1` is not in the range `[1, q)`.
+    pub fn from_secret(
+        params: Params,
+        u1: BigUint,
+        z: Option<RegistrationID>,
+    ) -> Result<Self, Error> {
+        if u1 == BigUint::ZERO || u1 >= params.q {
+            return Err(Error::InvalidSecret);
         }
+        // i = g1^u1 mod p
+        let i = params.g1.modpow(&u1, &params.p);
+        Ok(Self {
+            params,
+            i,
+            u1,
+            z,
+            spent: HashSet::new(),
+            hkdf: None,
+        })
     }
 
     /// Setting the value given by the issuer in registration process.
@@ -42,81 +116,228 @@ impl Spender {
         self.z = Some(registration_id);
     }
 
+    /// Clears the registration ID given by the issuer, e.g. for account closure or key rotation.
+    /// The spender has to register again before withdrawing coins.
+    ///
+    /// The memory of the registration ID is overwritten with zeros before it is dropped, on a
+    /// best-effort basis.
+    pub fn clear_registration(&mut self) {
+        if let Some(mut z) = self.z.take() {
+            let num_digits = (z.bits() as usize).div_ceil(32);
+            z.assign_from_slice(&vec![0u32; num_digits]);
+        }
+    }
+
     /// Returns a Withdrawal by computations with the withdrawal parameters given by Issuer.
     /// A challenge is returned together for the spender to further check the validity of the
     /// issued coin.
     ///
-    /// ## Panics
-    /// Panics if the spender has not call [set_registration_id()](crate::Spender::set_registration_id)
-    /// before (i.e. has not received an registration ID from issuer).
+    /// Returns [Error::NotRegistered] if the spender has not call
+    /// [set_registration_id()](crate::Spender::set_registration_id) before (i.e. has not received
+    /// an registration ID from issuer), [Error::InvalidSecret] if a secret value of the coin
+    /// (`s`, `u` or `v`) is zero, or [Error::NonInvertible] if the blinding value sampled for
+    /// the coin is not invertible (mod q).
     pub fn withdraw(
         &self,
         withdrawal_spender_params: WithdrawalParams,
-    ) -> (Withdrawal, WithdrawalChallenge) {
+    ) -> Result<(Withdrawal, WithdrawalChallenge), Error> {
+        self.withdraw_with_rng(withdrawal_spender_params, &mut rand::thread_rng())
+    }
+
+    /// Returns a Withdrawal as [withdraw](crate::Spender::withdraw) does, with the secret values
+    /// of the coin sampled from `rng` (e.g. `&mut dyn RngCore` supplied by a WASM host).
+    pub fn withdraw_with_rng<R: RngCore + ?Sized>(
+        &self,
+        withdrawal_spender_params: WithdrawalParams,
+        rng: &mut R,
+    ) -> Result<(Withdrawal, WithdrawalChallenge), Error> {
+        self.withdraw_coin(
+            withdrawal_spender_params,
+            None,
+            self.random_partial_coin(1, rng),
+        )
+    }
+
+    /// Returns a Withdrawal as [withdraw](crate::Spender::withdraw) does, with the secret values
+    /// of the coin derived from the seed of the spender and the `counter`, instead of being
+    /// sampled randomly. The same seed and counter regenerate the same coin given the same
+    /// withdrawal parameters and response from the issuer, so the counter should not be reused
+    /// for different withdrawals.
+    ///
+    /// Returns [Error::NotDeterministic] if the spender is not instantiated by
+    /// [new_deterministic](crate::Spender::new_deterministic).
+    pub fn withdraw_deterministic(
+        &self,
+        withdrawal_spender_params: WithdrawalParams,
+        counter: u64,
+    ) -> Result<(Withdrawal, WithdrawalChallenge), Error> {
+        let hkdf = self.hkdf.as_ref().ok_or(Error::NotDeterministic)?;
+        let counter = counter.to_le_bytes();
+        let derive =
+            |label: &[u8]| hkdf_to_scalar(hkdf, &[b"coin", &counter, label], &self.params.q);
         let partial_coin = PartialCoin {
-            s: random_number(&self.params.q),
-            x1: random_number(&self.params.q),
-            x2: random_number(&self.params.q),
-            u: random_number(&self.params.q),
-            v: random_number(&self.params.q),
+            s: derive(b"s"),
+            x1: derive(b"x1"),
+            x2: derive(b"x2"),
+            u: derive(b"u"),
+            v: derive(b"v"),
+            x_extra: Vec::new(),
         };
+        self.withdraw_coin(withdrawal_spender_params, None, partial_coin)
+    }
+
+    /// Returns a Withdrawal as [withdraw](crate::Spender::withdraw) does, for a coin that can be
+    /// shown (i.e. spent upon different challenges) up to `shows` times. Each show uses one of
+    /// the `shows` independent slots of the coin by calling the method
+    /// [spend_slot](crate::Spender::spend_slot), and the identity of the spender is revealed
+    /// only if a slot is used twice.
+    ///
+    /// Returns [Error::InvalidShows] if `shows` is zero.
+    pub fn withdraw_k_show(
+        &self,
+        withdrawal_spender_params: WithdrawalParams,
+        shows: u32,
+    ) -> Result<(Withdrawal, WithdrawalChallenge), Error> {
+        self.withdraw_k_show_with_rng(withdrawal_spender_params, shows, &mut rand::thread_rng())
+    }
+
+    /// Returns a Withdrawal as [withdraw_k_show](crate::Spender::withdraw_k_show) does, with the
+    /// secret values of the coin sampled from `rng`.
+    pub fn withdraw_k_show_with_rng<R: RngCore + ?Sized>(
+        &self,
+        withdrawal_spender_params: WithdrawalParams,
+        shows: u32,
+        rng: &mut R,
+    ) -> Result<(Withdrawal, WithdrawalChallenge), Error> {
+        if shows == 0 {
+            return Err(Error::InvalidShows);
+        }
+        self.withdraw_coin(
+            withdrawal_spender_params,
+            None,
+            self.random_partial_coin(shows, rng),
+        )
+    }
+
+    /// Returns a Withdrawal as [withdraw](crate::Spender::withdraw) does, with the metadata
+    /// (e.g. denomination) bound into the coin. The metadata can be learnt by the receiver
+    /// by calling the method [verify_and_extract](crate::Coin::verify_and_extract).
+    ///
+    /// The issuer does not learn the metadata in the withdrawal process.
+    pub fn withdraw_with_metadata(
+        &self,
+        withdrawal_spender_params: WithdrawalParams,
+        metadata: CoinMetadata,
+    ) -> Result<(Withdrawal, WithdrawalChallenge), Error> {
+        self.withdraw_with_metadata_with_rng(
+            withdrawal_spender_params,
+            metadata,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// Returns a Withdrawal as [withdraw_with_metadata](crate::Spender::withdraw_with_metadata)
+    /// does, with the secret values of the coin sampled from `rng`.
+    pub fn withdraw_with_metadata_with_rng<R: RngCore + ?Sized>(
+        &self,
+        withdrawal_spender_params: WithdrawalParams,
+        metadata: CoinMetadata,
+        rng: &mut R,
+    ) -> Result<(Withdrawal, WithdrawalChallenge), Error> {
+        self.withdraw_coin(
+            withdrawal_spender_params,
+            Some(metadata),
+            self.random_partial_coin(1, rng),
+        )
+    }
+
+    /// Samples the secret values of a coin with `shows` show slots.
+    fn random_partial_coin<R: RngCore + ?Sized>(&self, shows: u32, rng: &mut R) -> PartialCoin {
+        let q = &self.params.q;
+        // s, u and v must be non-zero, see withdraw_coin. x1 and x2 are non-zero as well, so
+        // that the commitment B = g1^x1 * g2^x2 is not 1 even if the generator is broken (e.g.
+        // yields only zeros).
+        PartialCoin {
+            s: random_nonzero(q, rng),
+            x1: random_nonzero(q, rng),
+            x2: random_nonzero(q, rng),
+            u: random_nonzero(q, rng),
+            v: random_nonzero(q, rng),
+            x_extra: (1..shows)
+                .map(|_| (random_nonzero(q, rng), random_nonzero(q, rng)))
+                .collect(),
+        }
+    }
+
+    fn withdraw_coin(
+        &self,
+        withdrawal_spender_params: WithdrawalParams,
+        metadata: Option<CoinMetadata>,
+        partial_coin: PartialCoin,
+    ) -> Result<(Withdrawal, WithdrawalChallenge), Error> {
+        let z = self.z.as_ref().ok_or(Error::NotRegistered)?;
+        // s = 0 degenerates the coin (A = 1), u = 0 is not invertible for the challenge c = cd/u,
+        // and v = 0 leaves the response of the issuer unblinded in the coin (c6 = r * u).
+        let zero = BigUint::ZERO;
+        if partial_coin.s == zero || partial_coin.u == zero || partial_coin.v == zero {
+            return Err(Error::InvalidSecret);
+        }
         // A = (i * g2) ^ s
         let a = (&self.i * &self.params.g2).modpow(&partial_coin.s, &self.params.p);
-        // B = g1^x2 * g2^x2
-        let b = self.params.g1.modpow(&partial_coin.x1, &self.params.p)
-            * self.params.g2.modpow(&partial_coin.x2, &self.params.p);
+        // B = g1^x1 * g2^x2, for each show slot
+        let commit = |x1: &BigUint, x2: &BigUint| {
+            (self.params.g1.modpow(x1, &self.params.p) * self.params.g2.modpow(x2, &self.params.p))
+                % &self.params.p
+        };
+        let b = commit(&partial_coin.x1, &partial_coin.x2);
+        let b_extra: Vec<BigUint> = partial_coin
+            .x_extra
+            .iter()
+            .map(|(x1, x2)| commit(x1, x2))
+            .collect();
         // zd = z^s
-        let zd = self
-            .z
-            .as_ref()
-            .unwrap()
-            .modpow(&partial_coin.s, &self.params.p);
+        let zd = z.modpow(&partial_coin.s, &self.params.p);
         // ad = a^u * g^v
-        let ad = withdrawal_spender_params
+        let ad = (withdrawal_spender_params
             .a
             .modpow(&partial_coin.u, &self.params.p)
-            * self.params.g.modpow(&partial_coin.v, &self.params.p);
-        // bd = b^(s * u) * A^v
-        let bd = withdrawal_spender_params
-            .b
-            .modpow(&(&partial_coin.s * &partial_coin.u), &self.params.p)
-            * a.modpow(&partial_coin.v, &self.params.p);
-        // cd = Hash(A,B,zd,ad,bd)
-        let challenge_d = hash_to_number(
-            self.params.scheme_key.as_bytes(),
-            &[
-                a.to_bytes_le(),
-                b.to_bytes_le(),
-                zd.to_bytes_le(),
-                ad.to_bytes_le(),
-                bd.to_bytes_le(),
-            ],
-        ) % &self.params.p;
+            * self.params.g.modpow(&partial_coin.v, &self.params.p))
+            % &self.params.p;
+        // bd = b^(s * u) * A^v, where s * u is reduced mod q so that the bit length of the
+        // secret exponent does not show in the time of the exponentiation. The result is the
+        // same, as b is an element of the subgroup of order q (i.e. b^q = 1).
+        let su = (&partial_coin.s * &partial_coin.u) % &self.params.q;
+        let bd = (withdrawal_spender_params.b.modpow(&su, &self.params.p)
+            * a.modpow(&partial_coin.v, &self.params.p))
+            % &self.params.p;
+        // cd = Hash(A,B,zd,ad,bd,metadata)
+        let bs: Vec<&BigUint> = std::iter::once(&b).chain(&b_extra).collect();
+        let challenge_d = coin_challenge_d(&self.params, &a, &bs, &zd, &ad, &bd, metadata.as_ref());
         // c = cd/u mod q
-        let challenge =
-            (&challenge_d * &partial_coin.u.modinv(&self.params.q).unwrap()) % &self.params.q;
+        let challenge = WithdrawalChallenge::blind(&challenge_d, &partial_coin.u, &self.params.q)?;
 
-        (
+        Ok((
             Withdrawal {
-                a_by_issuer: withdrawal_spender_params.a.clone(),
-                b_by_issuer: withdrawal_spender_params.b.clone(),
+                a_by_issuer: withdrawal_spender_params.a,
+                b_by_issuer: withdrawal_spender_params.b,
                 challenge_d,
                 a,
                 b,
+                b_extra,
                 zd,
                 ad,
                 bd,
+                metadata,
                 partial_coin,
             },
-            WithdrawalChallenge { c: challenge },
-        )
+            challenge,
+        ))
     }
 
     /// Verifies the withdrawal response from issuer where the response will be used to create
     /// a coin. This verification is an optional step in the protocol.
     ///
-    /// ## Panics
-    /// Panics if the spender has not call [set_registration_id()](crate::Spender::set_registration_id)
+    /// Returns false if the spender has not call [set_registration_id()](crate::Spender::set_registration_id)
     /// before (i.e. has not received an registration ID from issuer).
     pub fn verify_withdrawal_response(
         &self,
@@ -125,14 +346,13 @@ impl Spender {
         withdrawal_challenge: &WithdrawalChallenge,
         withdrawal_response: &WithdrawalResponse,
     ) -> bool {
+        let Some(z) = self.z.as_ref() else {
+            return false;
+        };
+
         // (i * g2)^r == z^c * b
         let lhs = (&self.i * &self.params.g2).modpow(&withdrawal_response.r, &self.params.p);
-        let rhs = (&self
-            .z
-            .as_ref()
-            .unwrap()
-            .modpow(&withdrawal_challenge.c, &self.params.p)
-            * &withdrawal.b_by_issuer)
+        let rhs = (&z.modpow(&withdrawal_challenge.c, &self.params.p) * &withdrawal.b_by_issuer)
             % &self.params.p;
         if lhs != rhs {
             return false;
@@ -149,30 +369,139 @@ impl Spender {
         true
     }
 
-    /// Makes a coin by the withdrawal response from issuer.
+    /// Makes a coin by the withdrawal response from issuer, and returns it together with the
+    /// [PartialCoin] for spending the coin later. The withdrawal will be consumed, as its values
+    /// are moved into the coin.
     pub fn make_coin(
         &self,
-        withdrawal: &Withdrawal,
+        withdrawal: Withdrawal,
         withdrawal_response: WithdrawalResponse,
-    ) -> Coin {
-        let c1 = withdrawal.a.clone();
-        let c2 = withdrawal.b.clone();
-        let c3 = withdrawal.zd.clone();
-        let c4 = withdrawal.ad.clone();
-        let c5 = withdrawal.bd.clone();
+    ) -> (Coin, PartialCoin) {
         // rd = ru + v mod q
         let c6 = (withdrawal_response.r * &withdrawal.partial_coin.u + &withdrawal.partial_coin.v)
             % &self.params.q;
-        let cd = withdrawal.challenge_d.clone();
-        Coin {
-            c1,
-            c2,
-            c3,
-            c4,
-            c5,
+        let coin = Coin {
+            c1: withdrawal.a,
+            c2: withdrawal.b,
+            c3: withdrawal.zd,
+            c4: withdrawal.ad,
+            c5: withdrawal.bd,
             c6,
-            cd,
-        }
+            cd: withdrawal.challenge_d,
+            metadata: withdrawal.metadata,
+            c2_extra: withdrawal.b_extra,
+        };
+        (coin, withdrawal.partial_coin)
+    }
+
+    /// Makes a coin by the withdrawal response from issuer, as [make_coin](crate::Spender::make_coin)
+    /// does, and bundles it with the [PartialCoin] for spending the coin later. The withdrawal
+    /// will be consumed.
+    pub fn make_coin_with_secrets(
+        &self,
+        withdrawal: Withdrawal,
+        withdrawal_response: WithdrawalResponse,
+    ) -> CoinWithSecrets {
+        let (coin, partial_coin) = self.make_coin(withdrawal, withdrawal_response);
+        CoinWithSecrets { coin, partial_coin }
+    }
+
+    /// Requests to refresh the old coin, i.e. exchange it for a new coin withdrawn with the
+    /// withdrawal parameters given by Issuer, e.g. for limiting the linkability of a coin held
+    /// for a long time. The old coin is spent upon a challenge bound to the withdrawal of the
+    /// new coin. The request is sent to the issuer, and the Withdrawal is kept for making the
+    /// new coin by the method [make_coin](crate::Spender::make_coin) with the response.
+    ///
+    /// Returns the errors of [withdraw](crate::Spender::withdraw).
+    pub fn request_refresh(
+        &self,
+        old_coin: Coin,
+        old_partial_coin: PartialCoin,
+        withdrawal_spender_params: WithdrawalParams,
+    ) -> Result<(Withdrawal, RefreshRequest), Error> {
+        self.request_refresh_with_rng(
+            old_coin,
+            old_partial_coin,
+            withdrawal_spender_params,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// Requests to refresh the old coin as [request_refresh](crate::Spender::request_refresh)
+    /// does, with the secret values of the new coin sampled from `rng`.
+    pub fn request_refresh_with_rng<R: RngCore + ?Sized>(
+        &self,
+        old_coin: Coin,
+        old_partial_coin: PartialCoin,
+        withdrawal_spender_params: WithdrawalParams,
+        rng: &mut R,
+    ) -> Result<(Withdrawal, RefreshRequest), Error> {
+        let (withdrawal, withdrawal_challenge) =
+            self.withdraw_with_rng(withdrawal_spender_params, rng)?;
+        let challenge = refresh_challenge(&old_coin, &withdrawal_challenge);
+        let spent_coin = self.spend(old_coin, old_partial_coin, &challenge);
+        Ok((
+            withdrawal,
+            RefreshRequest {
+                spent_coin,
+                withdrawal_challenge,
+            },
+        ))
+    }
+
+    /// Returns the one-time public key of the coin, whose secret key is derived from the secret
+    /// value of the spender and the secret values of the coin. It is bound into the coin
+    /// challenge by [CoinChallenge::new_with_onetime_key], and used for verifying the
+    /// transaction signed by [sign_transaction](crate::Spender::sign_transaction).
+    pub fn onetime_public_key(&self, partial_coin: &PartialCoin) -> BigUint {
+        self.params
+            .g
+            .modpow(&self.onetime_secret_key(partial_coin), &self.params.p)
+    }
+
+    /// Signs the transaction (e.g. the payment details agreed with the receiver) by the one-time
+    /// key of the coin, for non-repudiation of the payment. The signature can be verified by
+    /// [TransactionSignature::verify] with the one-time public key of the coin.
+    pub fn sign_transaction(
+        &self,
+        partial_coin: &PartialCoin,
+        message: &[u8],
+    ) -> TransactionSignature {
+        self.sign_transaction_with_rng(partial_coin, message, &mut rand::thread_rng())
+    }
+
+    /// Signs the transaction as [sign_transaction](crate::Spender::sign_transaction) does, with
+    /// the nonce of the signature sampled from `rng`.
+    pub fn sign_transaction_with_rng<R: RngCore + ?Sized>(
+        &self,
+        partial_coin: &PartialCoin,
+        message: &[u8],
+        rng: &mut R,
+    ) -> TransactionSignature {
+        let sk = self.onetime_secret_key(partial_coin);
+        let pk = self.params.g.modpow(&sk, &self.params.p);
+        let (e, s) = schnorr_sign(
+            &sk,
+            &self.params.g,
+            &self.params.p,
+            &self.params.q,
+            rng,
+            |r| TransactionSignature::challenge(&self.params, &pk, r, message),
+        );
+        TransactionSignature { e, s }
+    }
+
+    /// Derives the one-time secret key of the coin, sk = Hash(u1, s) (mod q).
+    fn onetime_secret_key(&self, partial_coin: &PartialCoin) -> BigUint {
+        hash_to_scalar(
+            &self.params.hmac_key(),
+            &[
+                ONETIME_KEY_TAG.to_vec(),
+                self.u1.to_bytes_le(),
+                partial_coin.s.to_bytes_le(),
+            ],
+            &self.params.q,
+        )
     }
 
     /// Spends the coin given challenge by verifier. This is supposed to
@@ -182,11 +511,78 @@ impl Spender {
         coin: Coin,
         partial_coin: PartialCoin,
         challenge: &CoinChallenge,
+    ) -> SpentCoin {
+        self.respond(
+            coin,
+            &partial_coin.s,
+            &partial_coin.x1,
+            &partial_coin.x2,
+            0,
+            challenge,
+        )
+    }
+
+    /// Spends the k-show coin on the show slot given challenge by verifier, as
+    /// [spend](crate::Spender::spend) does. Each slot should be used only once, otherwise the
+    /// identity of the spender can be revealed.
+    ///
+    /// Returns [Error::InvalidSlot] if the slot is not provisioned in the coin.
+    pub fn spend_slot(
+        &self,
+        coin: Coin,
+        partial_coin: &PartialCoin,
+        slot: u32,
+        challenge: &CoinChallenge,
+    ) -> Result<SpentCoin, Error> {
+        if slot >= coin.shows() {
+            return Err(Error::InvalidSlot);
+        }
+        let (x1, x2) = partial_coin.slot_secrets(slot).ok_or(Error::InvalidSlot)?;
+        Ok(self.respond(coin, &partial_coin.s, x1, x2, slot, challenge))
+    }
+
+    fn respond(
+        &self,
+        coin: Coin,
+        s: &BigUint,
+        x1: &BigUint,
+        x2: &BigUint,
+        slot: u32,
+        challenge: &CoinChallenge,
     ) -> SpentCoin {
         // r1 = d(u1)s + x1 mod q
-        let r1 = (&challenge.0 * &self.u1 * &partial_coin.s + &partial_coin.x1) % &self.params.q;
+        let r1 = (&challenge.0 * &self.u1 * s + x1) % &self.params.q;
         // r2 = ds + x2 mod q
-        let r2 = (&challenge.0 * &partial_coin.s + &partial_coin.x2) % &self.params.q;
-        SpentCoin { coin, r1, r2 }
+        let r2 = (&challenge.0 * s + x2) % &self.params.q;
+        SpentCoin { coin, r1, r2, slot }
+    }
+
+    /// Spends the coin given challenge by verifier, as [spend](crate::Spender::spend) does, but
+    /// refuses to spend the same coin twice. Returns [Error::AlreadySpent] if the coin has been
+    /// spent by this method before, which prevents the spender from double spending by accident.
+    pub fn spend_once(
+        &mut self,
+        coin: Coin,
+        partial_coin: PartialCoin,
+        challenge: &CoinChallenge,
+    ) -> Result<SpentCoin, Error> {
+        if !self.spent.insert(coin.fingerprint()) {
+            return Err(Error::AlreadySpent);
+        }
+        Ok(self.spend(coin, partial_coin, challenge))
+    }
+
+    /// Spends the coin in the bundle given challenge by verifier, as [spend](crate::Spender::spend)
+    /// does.
+    pub fn spend_with_secrets(
+        &self,
+        coin_with_secrets: CoinWithSecrets,
+        challenge: &CoinChallenge,
+    ) -> SpentCoin {
+        self.spend(
+            coin_with_secrets.coin,
+            coin_with_secrets.partial_coin,
+            challenge,
+        )
     }
 }
@@ -0,0 +1,52 @@
+//! Emits the metrics of the protocol steps by the `metrics` crate if the feature `metrics` is
+//! enabled. Without the feature, the functions do nothing and are optimized out.
+
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Counter of the spenders registered by [Issuer::register](crate::Issuer::register).
+pub(crate) const REGISTER: &str = "brands.register";
+
+/// Counter of the withdrawal responses returned by
+/// [Issuer::withdrawal_response](crate::Issuer::withdrawal_response).
+pub(crate) const WITHDRAWAL: &str = "brands.withdrawal";
+
+/// Histogram of the durations (in seconds) of verifying the coins by [Coin::verify](crate::Coin::verify).
+pub(crate) const VERIFY_DURATION: &str = "brands.verify.duration";
+
+/// Counter of the identities of the double spenders revealed.
+pub(crate) const DOUBLE_SPEND_DETECTED: &str = "brands.double_spend.detected";
+
+/// Increments the counter `name` by `value`.
+#[inline]
+pub(crate) fn count(name: &'static str, value: u64) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(name).increment(value);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (name, value);
+}
+
+/// Measures the duration of a protocol step, from [Timer::start] to [Timer::record].
+pub(crate) struct Timer {
+    #[cfg(feature = "metrics")]
+    start: Instant,
+}
+
+impl Timer {
+    #[inline]
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            start: Instant::now(),
+        }
+    }
+
+    /// Records the duration since the start into the histogram `name`.
+    #[inline]
+    pub(crate) fn record(self, name: &'static str) {
+        #[cfg(feature = "metrics")]
+        metrics::histogram!(name).record(self.start.elapsed().as_secs_f64());
+        #[cfg(not(feature = "metrics"))]
+        let _ = name;
+    }
+}
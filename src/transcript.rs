@@ -0,0 +1,34 @@
+//! Defines the transcript of spending a coin, for auditing the spending process.
+
+use crate::{
+    coin::{CoinChallenge, SpentCoin},
+    params::Params,
+};
+
+/// A transcript of spending a coin, created by calling the method
+/// [transcript](crate::SpentCoin::transcript). It contains the spent coin (i.e. the coin and
+/// the responses), the coin challenge, the public scheme parameters and the outcome of the
+/// verification when the transcript was assembled.
+///
+/// The transcript is self-contained, so that a third party can re-verify it independently,
+/// e.g. for adjudicating a double spending claim.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpendTranscript {
+    /// The spent coin, including the responses created by the spender.
+    pub spent_coin: SpentCoin,
+    /// The coin challenge created by the receiver.
+    pub challenge: CoinChallenge,
+    /// The public scheme parameters.
+    pub params: Params,
+    /// The outcome of the verification when the transcript was assembled.
+    pub verified: bool,
+}
+
+impl SpendTranscript {
+    /// Re-verifies the spent coin upon the coin challenge in the transcript. Returns true if
+    /// the spent coin is valid, regardless of the outcome recorded in the transcript.
+    pub fn verify(&self) -> bool {
+        self.spent_coin.verify(&self.challenge, &self.params)
+    }
+}
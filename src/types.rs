@@ -2,9 +2,37 @@
 
 use num_bigint::BigUint;
 
+use crate::{Error, Params};
+
 /// Identity of an actor in the scheme (i.e. a spender or a issuer).
 pub type Identity = BigUint;
 
 /// Registration Identifier provided by issuer to spender in registration process.
 /// This is unique to the pair issuer-spender.
 pub type RegistrationID = BigUint;
+
+/// Converts the identity into its canonical bytes, which are the little-endian bytes of the
+/// identity padded with zeros to [serialized_element_len](crate::Params::serialized_element_len)
+/// bytes, e.g. for storing the identity revealed by
+/// [reveal_identity](crate::SpentCoin::reveal_identity).
+pub fn identity_to_bytes(identity: &Identity, params: &Params) -> Vec<u8> {
+    let mut bytes = identity.to_bytes_le();
+    bytes.resize(params.serialized_element_len().max(bytes.len()), 0);
+    bytes
+}
+
+/// Converts the bytes created by [identity_to_bytes] back into the identity.
+///
+/// Returns [Error::DeserializationFailed] if the length of the bytes is not
+/// [serialized_element_len](crate::Params::serialized_element_len), or
+/// [Error::InvalidIdentity] if the identity is not an element of the subgroup of order `q`.
+pub fn identity_from_bytes(bytes: &[u8], params: &Params) -> Result<Identity, Error> {
+    if bytes.len() != params.serialized_element_len() {
+        return Err(Error::DeserializationFailed);
+    }
+    let identity = BigUint::from_bytes_le(bytes);
+    if !params.is_subgroup_element(&identity) {
+        return Err(Error::InvalidIdentity);
+    }
+    Ok(identity)
+}
@@ -0,0 +1,242 @@
+//! Defines the verifier used by coin receivers, which needs only the public data of an issuer.
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    coin::{check_issuer_key, Coin, CoinChallenge, CoinFormat, SpentCoin},
+    cryptographics::FixedBaseTable,
+    encoding::{write_biguint, write_bytes, Reader},
+    params::Params,
+    Error, Identity, VerifyError,
+};
+
+/// Domain tag of the hash in the commitment of an issuer's identity.
+const KEY_COMMITMENT_TAG: &[u8] = b"brands-rs/key-commitment";
+
+/// A verifier of the coins encoded by [Coin::to_bytes], which can be used as a trait object
+/// (e.g. `Box<dyn VerifyCoin>`) regardless of the issuer and the group of the coins, e.g. for
+/// routing the coins to the verifiers by the issuers.
+pub trait VerifyCoin {
+    /// Decodes the coin from the bytes and verifies it. Returns [VerifyError::MalformedCoin] if
+    /// the bytes cannot be decoded into a coin.
+    fn verify(&self, bytes: &[u8]) -> Result<(), VerifyError>;
+}
+
+/// Verifies coins on behalf of a receiver (e.g. a point-of-sale terminal). It carries the
+/// identity of the issuer (h) and the public scheme parameters, without any secret of the
+/// issuer, so that it can be distributed to receivers for verifying coins offline.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoinVerifier {
+    /// Identity of the issuer.
+    pub h: Identity,
+    /// The public scheme parameters.
+    pub params: Params,
+}
+
+impl CoinVerifier {
+    pub fn new(h: Identity, params: Params) -> Self {
+        Self { h, params }
+    }
+
+    /// Encodes the verifier into bytes, i.e. the identity of the issuer followed by the
+    /// length-prefixed encoding of the parameters by [Params::to_bytes], which is the public
+    /// data of the issuer accepted by [verify_serialized_coin].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_biguint(&mut buf, &self.h);
+        write_bytes(&mut buf, &self.params.to_bytes());
+        buf
+    }
+
+    /// Decodes a verifier from the bytes encoded by [CoinVerifier::to_bytes]. Returns
+    /// [Error::DeserializationFailed] if the bytes are malformed, or the errors of
+    /// [Params::validate] if the parameters are invalid.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(bytes);
+        let h = reader.read_biguint()?;
+        let params = Params::try_from(reader.read_bytes()?)?;
+        reader.finish()?;
+        Ok(Self { h, params })
+    }
+
+    /// Verifies if the coin is valid, i.e. it is issued by the issuer.
+    pub fn verify(&self, coin: &Coin) -> Result<(), VerifyError> {
+        coin.check(&self.h, &self.params)
+    }
+
+    /// Returns true if the identity of the issuer (h) and the parameters carried by the verifier
+    /// match the commitment published by the issuer (see
+    /// [Issuer::key_commitment](crate::Issuer::key_commitment)), e.g. for pinning the key of the
+    /// issuer obtained from an untrusted channel.
+    pub fn check_issuer_commitment(&self, commitment: &[u8; 32]) -> bool {
+        key_commitment(&self.h, &self.params) == *commitment
+    }
+
+    /// Verifies if the spent coin is valid upon the coin challenge. The underlying coin
+    /// is verified before the responses of the spent coin.
+    pub fn verify_spent(
+        &self,
+        spent_coin: &SpentCoin,
+        challenge: &CoinChallenge,
+    ) -> Result<(), VerifyError> {
+        spent_coin.verify_full(&self.h, challenge, &self.params)
+    }
+}
+
+impl VerifyCoin for CoinVerifier {
+    fn verify(&self, bytes: &[u8]) -> Result<(), VerifyError> {
+        let coin = Coin::from_bytes(bytes).map_err(|_| VerifyError::MalformedCoin)?;
+        CoinVerifier::verify(self, &coin)
+    }
+}
+
+/// Computes the commitment of the identity of the issuer (h), i.e. the domain-separated hash of
+/// `h` and the parameters in the encoding of [CoinVerifier::to_bytes].
+pub(crate) fn key_commitment(h: &Identity, params: &Params) -> [u8; 32] {
+    let mut buf = Vec::new();
+    write_biguint(&mut buf, h);
+    write_bytes(&mut buf, &params.to_bytes());
+    Sha256::new()
+        .chain_update(KEY_COMMITMENT_TAG)
+        .chain_update(buf)
+        .finalize()
+        .into()
+}
+
+/// Decodes the coin encoded by [Coin::to_bytes] and the public data of the issuer encoded by
+/// [CoinVerifier::to_bytes], and verifies the coin, e.g. for a service which receives both in
+/// bytes.
+///
+/// Returns [Error::DeserializationFailed] if either of the bytes is malformed, the errors of
+/// [CoinVerifier::from_bytes] if the public data of the issuer is invalid, or
+/// [Error::InvalidCoin] (or [Error::InvalidCoinInGroup] if the parameters are labelled) with
+/// the reason if the coin fails verification.
+pub fn verify_serialized_coin(coin_bytes: &[u8], issuer_public_bytes: &[u8]) -> Result<(), Error> {
+    let verifier = CoinVerifier::from_bytes(issuer_public_bytes)?;
+    let coin = Coin::from_bytes(coin_bytes)?;
+    verifier
+        .verify(&coin)
+        .map_err(|reason| verifier.params.invalid_coin(reason))
+}
+
+/// Verifies coins as [CoinVerifier] does, with the powers of the issuer's identity (h)
+/// precomputed in a table, so that verifying many coins against the same issuer is faster.
+/// The table takes about `16 * bits(q) / 4` group elements of memory.
+pub struct PrecomputedVerifier {
+    /// Identity of the issuer.
+    pub h: Identity,
+    /// The public scheme parameters.
+    pub params: Params,
+    table: FixedBaseTable,
+}
+
+impl PrecomputedVerifier {
+    /// Precomputes the table of the issuer's identity (h).
+    ///
+    /// Returns [VerifyError::InvalidIssuerKey] if `h` is not an element of the subgroup of
+    /// order `q` other than 1.
+    pub fn new(h: Identity, params: Params) -> Result<Self, VerifyError> {
+        check_issuer_key(&h, &params)?;
+        let table = FixedBaseTable::new(&h, params.q.bits(), &params.p);
+        Ok(Self { h, params, table })
+    }
+
+    /// Verifies if the coin is valid, i.e. it is issued by the issuer, as
+    /// [CoinVerifier::verify] does.
+    pub fn verify(&self, coin: &Coin) -> Result<(), VerifyError> {
+        coin.check_with(&self.params, CoinFormat::V2, |e| self.table.pow(e))
+    }
+}
+
+impl VerifyCoin for PrecomputedVerifier {
+    fn verify(&self, bytes: &[u8]) -> Result<(), VerifyError> {
+        let coin = Coin::from_bytes(bytes).map_err(|_| VerifyError::MalformedCoin)?;
+        PrecomputedVerifier::verify(self, &coin)
+    }
+}
+
+/// Verifies coins against the identities (h) of an issuer which rotates its secret key, so that
+/// the coins issued under the old keys remain valid while the new coins are issued under the
+/// new key. Each identity is registered with a key id chosen by the issuer (e.g. a version
+/// number).
+///
+/// A coin does not carry the key id of its issuer, so [IssuerKeyRing::verify] tries the
+/// identities from the most recently inserted one, at the cost of one verification per key
+/// tried. [IssuerKeyRing::verify_with] verifies against a single key if the key id is known out
+/// of band (e.g. sent along with the coin).
+#[derive(Clone)]
+pub struct IssuerKeyRing {
+    /// The public scheme parameters.
+    pub params: Params,
+    keys: Vec<(u32, Identity)>,
+}
+
+impl IssuerKeyRing {
+    pub fn new(params: Params) -> Self {
+        Self {
+            params,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Inserts the identity of the issuer under the key id, as the most recent key. The identity
+    /// previously inserted under the same key id is replaced.
+    ///
+    /// Returns [VerifyError::InvalidIssuerKey] if `h` is not an element of the subgroup of order
+    /// `q` other than 1.
+    pub fn insert(&mut self, key_id: u32, h: Identity) -> Result<(), VerifyError> {
+        check_issuer_key(&h, &self.params)?;
+        self.remove(key_id);
+        self.keys.push((key_id, h));
+        Ok(())
+    }
+
+    /// Removes the identity of the key id, e.g. after all coins issued under it have expired.
+    /// Returns the removed identity, if any.
+    pub fn remove(&mut self, key_id: u32) -> Option<Identity> {
+        let index = self.keys.iter().position(|(id, _)| *id == key_id)?;
+        Some(self.keys.remove(index).1)
+    }
+
+    /// Returns the identity of the key id, if any.
+    pub fn get(&self, key_id: u32) -> Option<&Identity> {
+        self.keys
+            .iter()
+            .find(|(id, _)| *id == key_id)
+            .map(|(_, h)| h)
+    }
+
+    /// Verifies if the coin is issued under any key of the ring, and returns the key id of the
+    /// key which it is issued under.
+    ///
+    /// Returns [VerifyError::InvalidIssuerKey] if the ring is empty, or the reason of the failure
+    /// against the most recent key if the coin is not issued under any of the keys.
+    pub fn verify(&self, coin: &Coin) -> Result<u32, VerifyError> {
+        let mut first_err = None;
+        for (key_id, h) in self.keys.iter().rev() {
+            match coin.check(h, &self.params) {
+                Ok(()) => return Ok(*key_id),
+                Err(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
+        Err(first_err.unwrap_or(VerifyError::InvalidIssuerKey))
+    }
+
+    /// Verifies if the coin is issued under the key of the key id.
+    ///
+    /// Returns [VerifyError::InvalidIssuerKey] if the key id is not in the ring.
+    pub fn verify_with(&self, key_id: u32, coin: &Coin) -> Result<(), VerifyError> {
+        let h = self.get(key_id).ok_or(VerifyError::InvalidIssuerKey)?;
+        coin.check(h, &self.params)
+    }
+}
+
+impl VerifyCoin for IssuerKeyRing {
+    fn verify(&self, bytes: &[u8]) -> Result<(), VerifyError> {
+        let coin = Coin::from_bytes(bytes).map_err(|_| VerifyError::MalformedCoin)?;
+        IssuerKeyRing::verify(self, &coin).map(|_| ())
+    }
+}
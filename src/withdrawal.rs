@@ -2,7 +2,11 @@
 
 use num_bigint::BigUint;
 
-use crate::coin::PartialCoin;
+use crate::{
+    coin::{coin_challenge_d, CoinMetadata, PartialCoin},
+    math::modinv_checked,
+    Error, Params,
+};
 
 /// A mathematic representations of a state of a coin withdrawn from issuer. In this
 /// state, the encapsulated information are not enough to form [Coin](crate::Coin) to
@@ -12,6 +16,11 @@ use crate::coin::PartialCoin;
 /// will be called in order to complete coin spending process.
 ///
 /// When the coin has been spent, this withdrawal is no longer useful.
+///
+/// The withdrawal can be serialized for resuming the withdrawal process later. It contains
+/// the secrets of the coin, and should be kept private by the spender.
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Withdrawal {
     /// a
     pub(crate) a_by_issuer: BigUint,
@@ -22,21 +31,105 @@ pub struct Withdrawal {
     pub(crate) a: BigUint,
     /// B
     pub(crate) b: BigUint,
+    /// B of the additional show slots of a k-show coin.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub(crate) b_extra: Vec<BigUint>,
     pub(crate) zd: BigUint,
     pub(crate) ad: BigUint,
     pub(crate) bd: BigUint,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) metadata: Option<CoinMetadata>,
     pub(crate) partial_coin: PartialCoin,
 }
 
+impl Withdrawal {
+    /// Returns the value `a` sent by the issuer in [WithdrawalParams], e.g. for recording what
+    /// the issuer sent when [verify_withdrawal_response](crate::Spender::verify_withdrawal_response)
+    /// fails.
+    ///
+    /// ```
+    /// use brands::{Issuer, Params, Spender};
+    /// use diffie_hellman_groups::MODPGroup5;
+    ///
+    /// let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    /// let issuer = Issuer::new(params.clone());
+    /// let mut spender = Spender::new(params.clone());
+    /// spender.set_registration_id(issuer.register(&spender.i).unwrap());
+    ///
+    /// let (withdrawal_params, withdrawal_response_params) = issuer.setup_withdrawal_params(&spender.i);
+    /// let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+    /// let withdrawal_response = issuer
+    ///     .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+    ///     .unwrap();
+    /// if !spender.verify_withdrawal_response(
+    ///     &issuer.h,
+    ///     &withdrawal,
+    ///     &withdrawal_challenge,
+    ///     &withdrawal_response,
+    /// ) {
+    ///     eprintln!(
+    ///         "invalid withdrawal response for a = {}, b = {}",
+    ///         withdrawal.issuer_a(),
+    ///         withdrawal.issuer_b()
+    ///     );
+    /// }
+    /// ```
+    pub fn issuer_a(&self) -> &BigUint {
+        &self.a_by_issuer
+    }
+
+    /// Returns the value `b` sent by the issuer in [WithdrawalParams]. See [Withdrawal::issuer_a].
+    pub fn issuer_b(&self) -> &BigUint {
+        &self.b_by_issuer
+    }
+
+    /// Recomputes the challenge sent to the issuer from the values stored in the withdrawal, as
+    /// [withdraw](crate::Spender::withdraw) computes it, e.g. for checking a persisted challenge
+    /// against corruption before verifying the response of the issuer upon it.
+    ///
+    /// Returns [Error::NonInvertible] if the blinding factor `u` of the withdrawal is corrupted
+    /// to be 0.
+    pub fn recompute_challenge(&self, params: &Params) -> Result<WithdrawalChallenge, Error> {
+        let bs: Vec<&BigUint> = std::iter::once(&self.b).chain(&self.b_extra).collect();
+        let challenge_d = coin_challenge_d(
+            params,
+            &self.a,
+            &bs,
+            &self.zd,
+            &self.ad,
+            &self.bd,
+            self.metadata.as_ref(),
+        );
+        WithdrawalChallenge::blind(&challenge_d, &self.partial_coin.u, &params.q)
+    }
+}
+
 /// A challenge created by spender to issuer during coin withdrawal process, by
 /// calling the method [withdraw](crate::Spender::withdraw).
 ///
 /// This challenge will be used from issuer to create response, by calling the method
 /// [withdrawal_response](crate::Issuer::withdrawal_response).
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WithdrawalChallenge {
     pub(crate) c: BigUint,
 }
 
+impl WithdrawalChallenge {
+    /// Computes the challenge c = cd/u (mod q) from the challenge of the coin `cd`.
+    pub(crate) fn blind(
+        challenge_d: &BigUint,
+        u: &BigUint,
+        q: &BigUint,
+    ) -> Result<WithdrawalChallenge, Error> {
+        let c = (challenge_d * modinv_checked(u, q)?) % q;
+        Ok(WithdrawalChallenge { c })
+    }
+}
+
 /// A repsonse to the challenge created by issuer.
 ///
 /// This will be used by the spender to make a coin, by calling the method
@@ -44,12 +137,15 @@ pub struct WithdrawalChallenge {
 ///
 /// The spender can also optionally verify the response beforehand, by calling
 /// the method [verify_withdrawal_response](crate::Spender::verify_withdrawal_response).
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WithdrawalResponse {
     pub(crate) r: BigUint,
 }
 
 /// Contains the parameters created by issuer. They are used by spender for
 /// creation of a [Withdrawal] during the coin withdrawal process.
+#[derive(Clone, PartialEq, Eq)]
 pub struct WithdrawalParams {
     pub(crate) a: BigUint,
     pub(crate) b: BigUint,
@@ -57,6 +153,12 @@ pub struct WithdrawalParams {
 
 /// Contains the parameters created by issuer. They are used by issuer for
 /// creation of a [WithdrawalResponse] during the coin withdrawal process.
+///
+/// The parameters must be used in only one withdrawal, otherwise the secret key of the
+/// issuer leaks. The issuer rejects the reused parameters with [Error::NonceReuse](crate::Error::NonceReuse).
+#[derive(Clone)]
 pub struct WithdrawalResponseParams {
     pub(crate) w: BigUint,
+    /// a = g^w, which identifies the parameters.
+    pub(crate) a: BigUint,
 }
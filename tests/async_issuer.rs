@@ -0,0 +1,103 @@
+#![cfg(feature = "async")]
+
+use std::{
+    future::Future,
+    pin::{pin, Pin},
+    task::{Context, Poll, Waker},
+};
+
+use brands::{
+    AsyncIssuer, BlockingIssuer, Error, Identity, Issuer, Params, RegistrationID, Spender,
+    WithdrawalChallenge, WithdrawalParams, WithdrawalResponse, WithdrawalResponseParams,
+};
+use diffie_hellman_groups::MODPGroup5;
+
+/// Polls the future on the current thread until it is ready.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// A future which is pending once before being ready, as a remote call would be.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+/// A dummy backend which answers after a round trip.
+struct RemoteIssuer(Issuer);
+
+impl AsyncIssuer for RemoteIssuer {
+    async fn register(&self, i: &Identity) -> Result<RegistrationID, Error> {
+        YieldOnce(false).await;
+        self.0.register(i)
+    }
+
+    async fn setup_withdrawal_params(
+        &self,
+        i: &Identity,
+    ) -> Result<(WithdrawalParams, WithdrawalResponseParams), Error> {
+        YieldOnce(false).await;
+        Ok(self.0.setup_withdrawal_params(i))
+    }
+
+    async fn withdrawal_response(
+        &self,
+        withdrawal: WithdrawalResponseParams,
+        challenge: &WithdrawalChallenge,
+    ) -> Result<WithdrawalResponse, Error> {
+        YieldOnce(false).await;
+        self.0.withdrawal_response(withdrawal, challenge)
+    }
+}
+
+/// Withdraws a coin from the issuer, and returns whether the coin is valid.
+async fn withdraw(issuer: &impl AsyncIssuer, h: &Identity, params: &Params) -> Result<bool, Error> {
+    let mut spender = Spender::new(params.clone());
+    spender.set_registration_id(issuer.register(&spender.i).await?);
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i).await?;
+    let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params)?;
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params.clone(), &withdrawal_challenge)
+        .await?;
+    let (coin, _) = spender.make_coin(withdrawal, withdrawal_response);
+
+    // The parameters are rejected when reused, as by the synchronous issuer.
+    assert!(matches!(
+        issuer
+            .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+            .await,
+        Err(Error::NonceReuse)
+    ));
+    Ok(coin.verify(h, params))
+}
+
+#[test]
+fn test_async_issuer() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+
+    let issuer = RemoteIssuer(Issuer::new(params.clone()));
+    let h = issuer.0.h.clone();
+    assert_eq!(block_on(withdraw(&issuer, &h, &params)), Ok(true));
+
+    let issuer = BlockingIssuer::from(Issuer::new(params.clone()));
+    let h = issuer.0.h.clone();
+    assert_eq!(block_on(withdraw(&issuer, &h, &params)), Ok(true));
+}
@@ -0,0 +1,15 @@
+use brands::{Issuer, Params, Spender, WithdrawalSession};
+use diffie_hellman_groups::MODPGroup5;
+
+fn main() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let issuer = Issuer::new(params.clone());
+    let mut spender = Spender::new(params);
+    spender.set_registration_id(issuer.register(&spender.i).unwrap());
+
+    let (withdrawal_params, _) = issuer.setup_withdrawal_params(&spender.i);
+    let session = WithdrawalSession::new(&spender, withdrawal_params);
+    let (session, _) = session.challenge().unwrap();
+    // The session has created the challenge already.
+    session.challenge();
+}
@@ -0,0 +1,22 @@
+use brands::{CoinChallenge, DenominatedLedger, DenominatedSpentCoin, Params, SpentCoin, Unit};
+use diffie_hellman_groups::MODPGroup5;
+
+enum Eur {}
+impl Unit for Eur {
+    const CODE: &'static [u8] = b"EUR";
+}
+
+enum Usd {}
+impl Unit for Usd {
+    const CODE: &'static [u8] = b"USD";
+}
+
+fn deposit(spent_coin: SpentCoin, challenge: CoinChallenge) {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let mut ledger = DenominatedLedger::<Usd>::new(params);
+    let spent_coin = DenominatedSpentCoin::<Eur>::new(spent_coin).unwrap();
+    // A EUR coin cannot be deposited into a USD ledger.
+    ledger.deposit(spent_coin, challenge).unwrap();
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use brands::{Issuer, Params, Spender, WithdrawalSession};
+use diffie_hellman_groups::MODPGroup5;
+
+fn main() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let issuer = Issuer::new(params.clone());
+    let mut spender = Spender::new(params);
+    spender.set_registration_id(issuer.register(&spender.i).unwrap());
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let session = WithdrawalSession::new(&spender, withdrawal_params);
+    let (_, withdrawal_challenge) = spender
+        .withdraw(issuer.setup_withdrawal_params(&spender.i).0)
+        .unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    // The session has not created the challenge yet.
+    session.finalize(withdrawal_response);
+}
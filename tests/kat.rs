@@ -0,0 +1,245 @@
+//! Known-answer tests, which pin the serialized coins, spent coins, and revealed identities
+//! created from fixed parameters and seeds, so that any change to the hashing, the serialization,
+//! or the encoding of the scheme is caught.
+
+use brands::{
+    identity_to_bytes, Coin, CoinChallenge, Issuer, Params, PartialCoin, Spender, SpentCoin,
+};
+use diffie_hellman_groups::MODPGroup5;
+use num_bigint::BigUint;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// A deterministic random number generator whose output is the concatenation of
+/// `SHA-256(seed || counter)` for the counter (8 bytes, little-endian) from 0. It draws the
+/// nonces of the issuer, which are not derived from the seeds of the scheme.
+struct KatRng {
+    seed: &'static [u8],
+    counter: u64,
+    buf: Vec<u8>,
+}
+
+impl KatRng {
+    fn new(seed: &'static [u8]) -> Self {
+        Self {
+            seed,
+            counter: 0,
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl RngCore for KatRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        while self.buf.len() < dest.len() {
+            let block = Sha256::new()
+                .chain_update(self.seed)
+                .chain_update(self.counter.to_le_bytes())
+                .finalize();
+            self.buf.extend_from_slice(&block);
+            self.counter += 1;
+        }
+        dest.copy_from_slice(&self.buf[..dest.len()]);
+        self.buf.drain(..dest.len());
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// The inputs of a known-answer vector.
+struct Vector {
+    /// The secret key of the issuer.
+    issuer_secret: u64,
+    /// The seed of the nonces of the issuer, see [KatRng].
+    issuer_seed: &'static [u8],
+    /// The seed of the spender, see [Spender::new_deterministic].
+    spender_seed: &'static [u8],
+    /// The counter of the withdrawal, see [Spender::withdraw_deterministic].
+    counter: u64,
+}
+
+/// The parameters of all vectors.
+fn params() -> Params {
+    Params::from_dh_group_with_generators::<MODPGroup5>(
+        "brandskey".to_string(),
+        BigUint::from(4u64),
+        BigUint::from(9u64),
+        BigUint::from(25u64),
+    )
+    .unwrap()
+}
+
+/// Withdraws the coin of the vector.
+fn withdraw(params: &Params, vector: &Vector) -> (Issuer, Spender, Coin, PartialCoin) {
+    let issuer = Issuer::from_secret(params.clone(), BigUint::from(vector.issuer_secret)).unwrap();
+    let mut spender = Spender::new_deterministic(params.clone(), vector.spender_seed);
+    spender.set_registration_id(issuer.register(&spender.i).unwrap());
+
+    let mut rng = KatRng::new(vector.issuer_seed);
+    let (withdrawal_params, withdrawal_response_params) = issuer
+        .setup_withdrawal_params_many(&spender.i, 1, &mut rng)
+        .pop()
+        .unwrap();
+    let (withdrawal, withdrawal_challenge) = spender
+        .withdraw_deterministic(withdrawal_params, vector.counter)
+        .unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+    (issuer, spender, coin, partial_coin)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn kat_withdraw() {
+    let params = params();
+    let vector = Vector {
+        issuer_secret: 0x0123_4567_89ab_cdef,
+        issuer_seed: b"kat/issuer/1",
+        spender_seed: b"kat/spender/1",
+        counter: 0,
+    };
+    let (issuer, _, coin, _) = withdraw(&params, &vector);
+    assert!(coin.verify(&issuer.h, &params));
+    assert_eq!(to_hex(&coin.to_bytes()), KAT_WITHDRAW_COIN);
+}
+
+#[test]
+fn kat_spend() {
+    let params = params();
+    let vector = Vector {
+        issuer_secret: 0x0123_4567_89ab_cdef,
+        issuer_seed: b"kat/issuer/2",
+        spender_seed: b"kat/spender/2",
+        counter: 1,
+    };
+    let (issuer, spender, coin, partial_coin) = withdraw(&params, &vector);
+    let challenge = CoinChallenge::new(b"kat/shop", &coin);
+    let spent_coin = spender.spend(coin, partial_coin, &challenge);
+    assert!(spent_coin
+        .verify_full(&issuer.h, &challenge, &params)
+        .is_ok());
+    assert_eq!(to_hex(&spent_coin.to_bytes()), KAT_SPEND_SPENT_COIN);
+    assert!(SpentCoin::from_bytes(&spent_coin.to_bytes()).is_ok());
+}
+
+#[test]
+fn kat_reveal() {
+    let params = params();
+    let vector = Vector {
+        issuer_secret: 0x0123_4567_89ab_cdef,
+        issuer_seed: b"kat/issuer/3",
+        spender_seed: b"kat/spender/3",
+        counter: 2,
+    };
+    let (_, spender, coin, partial_coin) = withdraw(&params, &vector);
+    let challenge = CoinChallenge::new(b"kat/shopA", &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+    let challenge_2 = CoinChallenge::new(b"kat/shopB", &coin);
+    let spent_coin_2 = spender.spend(coin, partial_coin, &challenge_2);
+
+    let identity = spent_coin.reveal_identity(&spent_coin_2, &params).unwrap();
+    assert_eq!(identity, spender.i);
+    assert_eq!(
+        to_hex(&identity_to_bytes(&identity, &params)),
+        KAT_REVEAL_IDENTITY
+    );
+}
+
+/// The coin of [kat_withdraw] encoded by [Coin::to_bytes].
+const KAT_WITHDRAW_COIN: &str = "\
+    c0000000a0164dfc1391d7ff310f85239c30b61349621f5e0d8bc293a4c0e075813162c4590d6f16c4deae4efb8f9345\
+    38d680389651e41145a8213038e729a9d7110fb88f94df929feca59ed544155761a0bbd3006f2ea08821463076e89ca9\
+    243c06caf73655be21398a00b8056a7c63efb8e62211d4872f22ed0a30746291848e0b0b8b3f17de8ba9e6950e346805\
+    891bbecd9d1594675973d82c044f912311eda340816dd4386a95a3e9f98107f93e63fd9247157a82bb26d79704d0b36f\
+    57954eaec000000040ce2bcd016b81bb5d48779bee72a846b68b298ca3f267680c2875acec3e4ee30fa45f10e4a8c0a6\
+    1feab61ec9bcdc9177523192d6af0fd0ae00a18df98c159db750b78aeacf0bedc4d6fb82730ba01892edbc1103cf5fb2\
+    b316d9a328bfc9b9401f7e90ec38b1c5eaa6337994cbe4fc5ab3753222c68d15f9b45c664c5a83359648e4a3fbaa7d8c\
+    ceb58ed0a18cc7106300c94e04a13c03027dd7c73c1563124e0afa9fb4a169e25b840c7cddc9573b2046b348d09b3886\
+    2b4069037be7aa22c00000009b26bbcae7df3054017ac6759943157d5458ee64b55a997f4e5d1cd01b4098b20178816c\
+    f7e25326ec1fd418d98004b9651410bbc1192a65ac0009820aaa984795535416ab4b43ca251b8e85b0f13bc2fef64465\
+    6bea409c5eae785cc774fac8f3eb70ca575602e8ad6c4bbc6320c087b707330043f3dea453f2d7bb14cd76507fc3ad07\
+    d5aebf409e49f614674fea34497416061fc6b2408dd7873ee2c893c330626630bf35adceb7ee8abbd7d16e4210d4cfb6\
+    ad1a5d8c3a16bac9bdd9926dc0000000521b15ba92dc037b4035fc2c1b273858b38a555d28b9aaa20a1904f62f2f2d32\
+    3515e2f3980a0ad77d62ffebc919b42d64948d1f089a8f3c5807d81fb51e1fb9d4b706334f70b446b35d0b12b78ed35a\
+    140b3936f740fc9863d2a669c420845e9db1e6fe1b60dc9b872789cd1a6e0202a53186788771a29851554dff34b560c3\
+    2add16cb06d2c2dfcc6656fae330552da45aa18e11ce53b2fa8eac1958d536d36409b578f584e867aab3176b8d187463\
+    d93c8f4e655ba0d1a5a773f92c328203c00000007f63d880a3463d4a9dca7989fafbfe48526fca6d7264123861680b3f\
+    1e6bd9cdfa08f56bd9966640d081dbb5cf2cb8dbf0c21e596110b3295e2d9b1ee3a3e084c0edd8d13c0d8d12753cd74b\
+    cb3d3a10582878e3ca441ba6118e33d7eabbe0bf4494fc0456f908318ac5dc34657907f1980298f1e7ebccd431de085b\
+    7689a7984f2e699949612fd8ac3ff62fbd305c0a47dcd4de8bf42fedf8ed118d80ab67099f838bc37ce350d98e53ec89\
+    b60ffab9733fd321775a2b928351438f217c4636c000000078eecf156ea1d05b9285bbfd669cf228ac7c8f022ba29b03\
+    ea457696f78bccd41fde226f3b92c5c539d9d2972b6ce9c8ba9d200c3ffd53bf46c273172aedb62b9fab9041416b5ac5\
+    3dac168ec89b199c2bfca4986c425a43246a52ebc70185f77198980b9003e453c79bcfb6ccb851e6419e5037e9c2344f\
+    d1b08f60e3b905abeac307e8476d92dd32af41da10d9aafab3815c2765916f6d80bac4e32f02c1ec094f031f2dd2b714\
+    a8650bd3ab4c56365c4fe7756fa8b5d934800ddbe457b707c00000004c29b05bf131e368b50b0bee72301f5d88286557\
+    e44f5aaa5c93d8ab282f40f6259631b643f64017a4006660d7f0f434a6670bb812c99906c31614d3bceedaa89dea5d7a\
+    20448dd8ca360c1cc938e14be7ad14110dc1aa2914b08e536b90f02c66bfa6b324d9222d00226205e2513f556e421262\
+    321f6facecad91423cef3bc8127fd8186673ccaa77583855ddc5c821dc2c9cd216ed93283f352d75c0ee1240fa5a3468\
+    7430b08617788d8bbd5fa0387256963f6686105428533eb9e79fae51";
+
+/// The spent coin of [kat_spend] encoded by [SpentCoin::to_bytes].
+const KAT_SPEND_SPENT_COIN: &str = "\
+    5c050000c0000000e20d31dbcedae5420af70d4dfafca4852fb95c74ebf970d146bc9d239d2fb3ec027722d3daa42737\
+    ee31ba2a39e55ddc0ec712793aea5d9ca2796857d7c3363621366ea8c55ffd15b234bf92abe054797e74ddab41a1a4bf\
+    dca38a67671b1bcacd6322506fd36ae773a897bf4fb6f7b657b30735f867e271a3f674bad15a55a009f9cfe02f85c1a4\
+    13c8b7461bcbcb90f709bd96498f96beda5043d1ce7d887bbc275517d2f1bd22b4cb4d261753dea71da49f3739cc9492\
+    11108ac451e59c58c0000000e5a41f53cb5901578ccbc16e2ccdb611b5c21577306289b35f2d7c400c8bb54339e128db\
+    dc5e54af16ebfdd158281bf2198b9f24836ecdf04d1d7594323f597bb21d0bd5f182539c6e4dc008781e44f3a9542c2a\
+    195614de65cee8b703027b6f380dae5361c008a8d15e04d0da47920160feddf959ce68223a72f68f5c1a18ed8b0a59b1\
+    b844ecdefaad45a33edadeff27b76394aba3b676d739289c8c558bd8882bba68b43b269891f28809a4692145bc554b4b\
+    08afb251c028d6eb7e489e51c000000021b683739d873ab7812e72d9dcddd33a4983814937cf3f74efd1ed3f43215600\
+    d208ca5fa73132befc4607ca5b1fcbb46e85451c023ca81afc29e33d33e1eb26cf6dd743d293a5404ba766dcedb89250\
+    0012a1eea9ce887b840fafbff7c9bcdc4f5bd53c61b9e395fcc7d5500831be8004bc0d7c2c95481c060f50514206bc13\
+    bfabd99cd475b38487724d1f6ae7ea5bc02b94d58ebf91091f5e0ca8d8e79596a2ab95302f3c5a011be4bff382cf2c4a\
+    a08372099f27be08c0e33eb9e1641bf9c0000000371586a80f4a2b89f1a92ed1bada6346ca3046f331d133328e263ff9\
+    29f5a711c535de4edad6a2dbc9475d26bea4b31adfb59dae02e8ebf12594b0a28ec7b8b39e3f3c63eb06830bf7f27587\
+    98ce367e75041b3bb9680694dff949d17f198957cc898ddf052e8817c684f2851e3de76e67001673e7f2ab1ddde4fa2f\
+    295cbcdee4cfa7c36719e5a85d0b5b8c03d1305ba350d4a4412a0479d1f410ec0ae4541ea46729fa704196b05a3d85c9\
+    af349b8170841ef547681f4bfed0d86e2e482e8dc0000000b42605ac249df05d5402990772ce5f037b62ec5a51db1e55\
+    0fcd9acd2e2edfbb539ae37fe2ab6b08713b0f74802c4fd132327d46ca7058687194f24b50a5c985867e4f154fb5983d\
+    b30ce1eb7236fb3bb62a4677c405dbaa36dd45a0b5bdeb6d87ca529a35bf407cad715523ed9d2fde40a8fe778f136d7b\
+    afd63716d9297d5a113ce7111d9166a5f4c4b8e2c86d53d7ce7949e946f21d9c073f6c8bb286fe4194f4b228f79f973c\
+    f9afe8bdade24c7a53616cfda83fc8bda63990b407071ed0c00000004a3434cdbf82853363ec9e097422739c22515ed5\
+    3a2f8692cc1e55b977c347a8ca211771c172667ec4374f7039e1f10f88fad062afccd2ea953580e8a6aa88096ce48a73\
+    39c9b3443320a2c7aafbb03cfe7858c1390efac6f86fb4fadd01bd86925a397c171d6b36b8003a21e38754190adc736b\
+    edf67a46be601cc019d84b5f72f0cc028b7bd0c90149f14bc7ba71b0141041f9aa3b8a0eb95b3b6e22eda52c49520695\
+    d60b050517f3d6a17c16e2bf6db9cef6b32f76866541b51ee953c86fc00000005efe9cccad923b4cdd98091f4b84f252\
+    c534609bc3e06a72f0d421484596402c7eae0877554b4862ccbedb23f81fc82ce2eff2fb35c5ea97696769b7ae5fbac0\
+    95964720c12caf2aa1d90ad72cbcf9ade8199976110116e9d494e1d1b98c8ca8c08511f7308ab4701372863da058f904\
+    9bf217f1e1abe2365172719395c0c9a3d8f4a011fdc584d459a50d42bc0bc81fc613fe8cf35d868975d90e765743d2b2\
+    d9622275ff51a6ca92327c05c0b27505c4c9201c1b159330186e268c9659ad65c000000089020f6f95ce7ed7eef4e7c2\
+    e4db51c0619a935d100a166e5229545ce6efc613ea2d232e50df503c851d539772518418d091db35af49cf1dd5e159b3\
+    6167d66b399cd65547bcb1f253a707fc46fd019d55f6177a41653bd6927bf1c352d0a8f019970597813a528a8fe62a96\
+    ab3c415d063bfc09ee6305fe463055dbb0991af6819df50f70a30a2b2fcc65bac5bf3c17adbac536b981d54c326a6305\
+    159b0caa69d15637c87ca76669a0528a964f8a1291e3e70dc8c2041074f8dd4b26b23721c000000059a2f880cfd65901\
+    f522d4c01799bb002b8f5d75e7455a857381b2be6c55050d64ad4102701b208f67378ad874e39c8e884929f353614de9\
+    db4fb9676a1b727dea2ddbd928226c53f029231c8b8b2129f82189b8cd15c043cc3c86949d61d5d962c1cb7e0f7689df\
+    831d6cfa45f75cd716dd1bf29348cfe3d09343bee5c6f29724ab882d4d05f90c18671e9f35c6ca3f9626a2e2b98258fd\
+    8fbd1ecba5f3aeb842946156252b8da5ab3351af837fadf6660f8833c019d0a00ebd4552bc09633600000000";
+
+/// The identity revealed in [kat_reveal] encoded by [identity_to_bytes].
+const KAT_REVEAL_IDENTITY: &str = "\
+    628680ad7ac45e4160223347dd40aa0b85ec070a14d9382e712e2b6f38f658a5f403294ee99d56b472a3faf52dd85d4a\
+    6762735b1c6b9828a4cf94cd84f19a21a9beaaa28825a2e5bc0e2300c5f9899da30c7a9da8eaea327d6e972d94aa5f43\
+    3f874ce6a948d307c61df09c202c42d60ec1c1115e8843e5b7d87e8aa557ce8f886395538790b6ab614bf2f1f8ee7e9a\
+    c8bf99a9bcfb946d6b585d66cdcd86bcd7988344ffc5412a8ec9b05b993524e72d4937e318270b97c1ad87d4a5af07fc";
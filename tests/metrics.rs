@@ -0,0 +1,88 @@
+#![cfg(feature = "metrics")]
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use brands::{CoinChallenge, Issuer, Params, Spender};
+use diffie_hellman_groups::MODPGroup5;
+use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+
+/// A recorder which keeps the values of the counters in memory.
+#[derive(Default)]
+struct CounterRecorder {
+    counters: Mutex<HashMap<String, Arc<AtomicU64>>>,
+}
+
+impl CounterRecorder {
+    fn get(&self, name: &str) -> u64 {
+        self.counters
+            .lock()
+            .unwrap()
+            .get(name)
+            .map_or(0, |counter| counter.load(Ordering::SeqCst))
+    }
+}
+
+impl Recorder for CounterRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        let counter = self
+            .counters
+            .lock()
+            .unwrap()
+            .entry(key.name().to_string())
+            .or_default()
+            .clone();
+        Counter::from_arc(counter)
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+#[test]
+fn test_metrics_double_spend() {
+    let recorder = CounterRecorder::default();
+    metrics::with_local_recorder(&recorder, || {
+        let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+        let issuer = Issuer::new(params.clone());
+        let mut spender = Spender::new(params.clone());
+        spender.set_registration_id(issuer.register(&spender.i).unwrap());
+
+        let (withdrawal_params, withdrawal_response_params) =
+            issuer.setup_withdrawal_params(&spender.i);
+        let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+        let withdrawal_response = issuer
+            .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+            .unwrap();
+        let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+        assert_eq!(recorder.get("brands.register"), 1);
+        assert_eq!(recorder.get("brands.withdrawal"), 1);
+
+        let challenge = CoinChallenge::new("shopA".as_bytes(), &coin);
+        let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+        let challenge_2 = CoinChallenge::new("shopB".as_bytes(), &coin);
+        let spent_coin_2 = spender.spend(coin, partial_coin, &challenge_2);
+        assert_eq!(recorder.get("brands.double_spend.detected"), 0);
+
+        spent_coin.reveal_identity(&spent_coin_2, &params).unwrap();
+        assert_eq!(recorder.get("brands.double_spend.detected"), 1);
+        issuer
+            .reveal_identities(&[spent_coin, spent_coin_2])
+            .unwrap();
+        assert_eq!(recorder.get("brands.double_spend.detected"), 2);
+    });
+}
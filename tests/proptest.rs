@@ -0,0 +1,101 @@
+use brands::{
+    Coin, CoinChallenge, DepositLedger, DepositOutcome, Issuer, Params, PartialCoin, Spender,
+};
+use diffie_hellman_groups::{MODPGroup, MODPGroup5};
+use num_bigint::BigUint;
+use proptest::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Creates the parameters with fixed generators, which are cheaper than random ones.
+fn params() -> Params {
+    Params::from_dh_group_with_generators::<MODPGroup5>(
+        "brandskey".to_string(),
+        BigUint::from(4u64),
+        BigUint::from(9u64),
+        BigUint::from(25u64),
+    )
+    .unwrap()
+}
+
+/// Creates an issuer and a spender derived from the seeds, and withdraws a coin deterministically.
+fn withdraw_seeded(
+    params: &Params,
+    issuer_seed: u64,
+    spender_seed: &[u8],
+    counter: u64,
+) -> (Issuer, Spender, Coin, PartialCoin) {
+    let q = MODPGroup5::sophie_garmain_prime();
+    let x = BigUint::from(issuer_seed) % (&q - 1u64) + 1u64;
+    let issuer = Issuer::from_secret(params.clone(), x).unwrap();
+    let mut spender = Spender::new_deterministic(params.clone(), spender_seed);
+    spender.set_registration_id(issuer.register(&spender.i).unwrap());
+
+    let mut rng = StdRng::seed_from_u64(issuer_seed);
+    let (withdrawal_params, withdrawal_response_params) = issuer
+        .setup_withdrawal_params_many(&spender.i, 1, &mut rng)
+        .pop()
+        .unwrap();
+    let (withdrawal, withdrawal_challenge) = spender
+        .withdraw_deterministic(withdrawal_params, counter)
+        .unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+    (issuer, spender, coin, partial_coin)
+}
+
+proptest! {
+    // Each case runs a full withdrawal in a 1536-bit group.
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn prop_honest_spend_verifies_and_hides_identity(
+        issuer_seed in any::<u64>(),
+        spender_seed in proptest::collection::vec(any::<u8>(), 0..64),
+        counter in any::<u64>(),
+        message in proptest::collection::vec(any::<u8>(), 0..256),
+    ) {
+        let params = params();
+        let (issuer, spender, coin, partial_coin) =
+            withdraw_seeded(&params, issuer_seed, &spender_seed, counter);
+        prop_assert!(coin.verify(&issuer.h, &params));
+
+        let challenge = CoinChallenge::new(&message, &coin);
+        let spent_coin = spender.spend(coin, partial_coin, &challenge);
+        prop_assert_eq!(spent_coin.verify_full(&issuer.h, &challenge, &params), Ok(()));
+
+        let mut ledger = DepositLedger::new(params);
+        prop_assert!(issuer.reveal_identities(std::slice::from_ref(&spent_coin)).unwrap().is_empty());
+        prop_assert_eq!(ledger.deposit(spent_coin, challenge), Ok(DepositOutcome::Accepted));
+    }
+
+    #[test]
+    fn prop_double_spend_reveals_identity(
+        issuer_seed in any::<u64>(),
+        spender_seed in proptest::collection::vec(any::<u8>(), 0..64),
+        counter in any::<u64>(),
+        message in proptest::collection::vec(any::<u8>(), 0..256),
+        message_2 in proptest::collection::vec(any::<u8>(), 0..256),
+    ) {
+        prop_assume!(message != message_2);
+        let params = params();
+        let (issuer, spender, coin, partial_coin) =
+            withdraw_seeded(&params, issuer_seed, &spender_seed, counter);
+        prop_assert!(coin.verify(&issuer.h, &params));
+
+        let challenge = CoinChallenge::new(&message, &coin);
+        let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+        let challenge_2 = CoinChallenge::new(&message_2, &coin);
+        let spent_coin_2 = spender.spend(coin, partial_coin, &challenge_2);
+
+        prop_assert_eq!(spent_coin.reveal_identity(&spent_coin_2, &params), Ok(spender.i.clone()));
+
+        let mut ledger = DepositLedger::new(params);
+        prop_assert_eq!(ledger.deposit(spent_coin, challenge), Ok(DepositOutcome::Accepted));
+        match ledger.deposit(spent_coin_2, challenge_2) {
+            Ok(DepositOutcome::DoubleSpend(event)) => prop_assert_eq!(event.identity, spender.i),
+            outcome => prop_assert!(false, "unexpected outcome {:?}", outcome),
+        }
+    }
+}
@@ -1,5 +1,41 @@
-use brands::{CoinChallenge, Issuer, Params, PartialCoin, Spender};
-use diffie_hellman_groups::MODPGroup5;
+// The tests exchange the values of the scheme in JSON, which requires the `serde` feature.
+#![cfg(feature = "serde")]
+
+use brands::{
+    identity_from_bytes, identity_to_bytes, verify_serialized_coin, Coin, CoinChallenge,
+    CoinFormat, CoinMetadata, CoinProof, CoinVerifier, CoinWarning, CoinWithSecrets,
+    ConcurrentDepositLedger, DenominatedLedger, DenominatedSpentCoin, Denomination, DepositLedger,
+    DepositOutcome, Endianness, Error, Issuer, IssuerKeyRing, Params, ParamsBuilder, PartialCoin,
+    PrecomputedVerifier, Receipt, Receiver, ResearchRecord, Settlement, SettlementResult,
+    SpendTranscript, Spender, SpentCoin, TransactionSignature, Unit, VerifyCoin, VerifyError,
+    Withdrawal, WithdrawalChallenge, WithdrawalResponse, WithdrawalSession,
+};
+use diffie_hellman_groups::{MODPGroup, MODPGroup5};
+use num_bigint::{BigUint, RandBigInt};
+use rand::{rngs::StdRng, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use subtle::ConstantTimeEq;
+
+/// Creates an issuer and a spender who has registered with the issuer.
+fn setup_accounts(params: &Params) -> (Issuer, Spender) {
+    let issuer = Issuer::new(params.clone());
+    let mut spender = Spender::new(params.clone());
+    spender.set_registration_id(issuer.register(&spender.i).unwrap());
+    (issuer, spender)
+}
+
+/// Runs the withdrawal process and returns the coin with its partial coin for spending.
+fn withdraw_coin(issuer: &Issuer, spender: &Spender) -> (Coin, PartialCoin) {
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+    (coin, partial_coin)
+}
 
 #[test]
 fn test_double_spent_coin_lifecycle() {
@@ -10,17 +46,18 @@ fn test_double_spent_coin_lifecycle() {
     println!("Spender Identity: {}", spender.i);
 
     // Account Setup
-    spender.set_registration_id(issuer.register(&spender.i));
+    spender.set_registration_id(issuer.register(&spender.i).unwrap());
 
     // Withdraw a coin
     // 1. Issuer setup parameters
     let (withdrawal_params, withdrawal_response_params) =
         issuer.setup_withdrawal_params(&spender.i);
     // 2. Spender creates a challenge for issuer
-    let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
     // 3. Issuer responses
-    let withdrawal_response =
-        issuer.withdrawal_response(withdrawal_response_params, &withdrawal_challenge);
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
     // 4. (Optional) Spender verifies the response
     assert!(spender.verify_withdrawal_response(
         &issuer.h,
@@ -29,7 +66,7 @@ fn test_double_spent_coin_lifecycle() {
         &withdrawal_response
     ));
     // 5. Spender makes a coin from the response
-    let coin = spender.make_coin(&withdrawal, withdrawal_response);
+    let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
 
     // Spend a coin
     // 1. Receiver verifies the coin
@@ -37,7 +74,6 @@ fn test_double_spent_coin_lifecycle() {
     // 2. Receiver challenges the spender
     let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
     // 3. Spender responds
-    let partial_coin = PartialCoin::from(withdrawal);
     let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
     // 4. Receiver verifies the spent coin
     assert!(spent_coin.verify(&challenge, &params));
@@ -56,7 +92,2818 @@ fn test_double_spent_coin_lifecycle() {
     // !! Issuer found same coin has been spent !!
 
     // Recover the identity of the spender
-    let i = spent_coin.reveal_identity(&spent_coin_2, &params);
+    let i = spent_coin.reveal_identity(&spent_coin_2, &params).unwrap();
     println!("Double spender is: {}", i);
     assert_eq!(i, spender.i);
 }
+
+#[test]
+fn test_reveal_identity_same_challenge() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    // Spending twice upon the same challenge makes the difference of the responses
+    // a multiple of q, which has no inverse.
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+    let spent_coin_2 = spender.spend(coin, partial_coin, &challenge);
+
+    assert_eq!(
+        spent_coin.reveal_identity(&spent_coin_2, &params),
+        Err(Error::SameChallenge)
+    );
+
+    // The spent coins of different coins.
+    let (other_coin, other_partial_coin) = withdraw_coin(&issuer, &spender);
+    let other_spent_coin = spender.spend(other_coin, other_partial_coin, &challenge);
+    assert_eq!(
+        spent_coin.reveal_identity(&other_spent_coin, &params),
+        Err(Error::DifferentCoins)
+    );
+}
+
+#[test]
+fn test_coin_verifier() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    // The verifier is distributed to receivers in serialized form.
+    let verifier = CoinVerifier::new(issuer.h.clone(), params.clone());
+    let serialized = serde_json::to_string(&verifier).unwrap();
+    let verifier: CoinVerifier = serde_json::from_str(&serialized).unwrap();
+
+    assert_eq!(verifier.verify(&coin), Ok(()));
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin, &challenge);
+    assert_eq!(verifier.verify_spent(&spent_coin, &challenge), Ok(()));
+
+    // Reject the spent coin upon a different challenge.
+    let challenge_2 = CoinChallenge::new("shopB-payment-item-1718193571".as_bytes(), &coin);
+    assert_eq!(
+        verifier.verify_spent(&spent_coin, &challenge_2),
+        Err(VerifyError::ResponseEqnFailed)
+    );
+
+    // Reject the coin issued by another issuer.
+    let other_verifier = CoinVerifier::new(Issuer::new(params.clone()).h, params);
+    assert_eq!(
+        other_verifier.verify(&coin),
+        Err(VerifyError::SignatureEqnFailed)
+    );
+    assert_eq!(
+        other_verifier.verify_spent(&spent_coin, &challenge),
+        Err(VerifyError::SignatureEqnFailed)
+    );
+}
+
+#[test]
+fn test_long_scheme_keys_separated() {
+    // Two keys longer than the HMac block size (64 bytes) which differ only at the end.
+    let key_1 = format!("{}-1", "k".repeat(100));
+    let key_2 = format!("{}-2", "k".repeat(100));
+
+    let params_1 = Params::from_dh_group::<MODPGroup5>(key_1);
+    let mut value = serde_json::to_value(&params_1).unwrap();
+    value["scheme_key"] = serde_json::Value::String(key_2);
+    let params_2: Params = serde_json::from_value(value).unwrap();
+
+    let (issuer, spender) = setup_accounts(&params_1);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    assert!(coin.verify(&issuer.h, &params_1));
+    let verifier = CoinVerifier::new(issuer.h.clone(), params_2);
+    assert_eq!(verifier.verify(&coin), Err(VerifyError::ChallengeMismatch));
+}
+
+#[test]
+fn test_spent_coin_conversions() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin, &challenge);
+
+    let inner_coin = Coin::from(&spent_coin);
+    assert!(inner_coin == coin);
+    assert!(inner_coin.verify(&issuer.h, &params));
+
+    // The rebuilt spent coin refers to the same coin, but it is not valid since
+    // the responses are not created upon the challenge.
+    let rebuilt = SpentCoin::from_parts(inner_coin, BigUint::from(1u64), BigUint::from(2u64));
+    assert!(rebuilt == spent_coin);
+    assert!(!rebuilt.verify(&challenge, &params));
+}
+
+#[test]
+fn test_spend_coin_with_secrets() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let coin_with_secrets = spender.make_coin_with_secrets(withdrawal, withdrawal_response);
+
+    // Only the coin is shared with the receiver.
+    let coin = coin_with_secrets.coin.clone();
+    assert!(coin.verify(&issuer.h, &params));
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = spender.spend_with_secrets(coin_with_secrets, &challenge);
+    assert!(spent_coin.verify(&challenge, &params));
+}
+
+#[test]
+fn test_spend_transcript() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin, &challenge);
+
+    let transcript = spent_coin.transcript(&challenge, &params);
+    let serialized = serde_json::to_string(&transcript).unwrap();
+    let transcript: SpendTranscript = serde_json::from_str(&serialized).unwrap();
+    assert!(transcript.verified);
+    assert!(transcript.verify());
+
+    // The transcript of a claim upon a different challenge does not verify.
+    let challenge_2 = CoinChallenge::new("shopB-payment-item-1718193571".as_bytes(), &coin);
+    let transcript = spent_coin.transcript(&challenge_2, &params);
+    let serialized = serde_json::to_string(&transcript).unwrap();
+    let transcript: SpendTranscript = serde_json::from_str(&serialized).unwrap();
+    assert!(!transcript.verified);
+    assert!(!transcript.verify());
+}
+
+#[test]
+fn test_params_with_generators() {
+    let g = BigUint::from(4u64);
+    let g1 = BigUint::from(9u64);
+    let g2 = BigUint::from(25u64);
+    let params = Params::from_dh_group_with_generators::<MODPGroup5>(
+        "brandskey".to_string(),
+        g.clone(),
+        g1.clone(),
+        g2.clone(),
+    )
+    .unwrap();
+
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+    assert!(coin.verify(&issuer.h, &params));
+
+    // p - 1 has order 2, which is not in the subgroup of order q.
+    let p_minus_1 = MODPGroup5::prime_modulus() - 1u64;
+    assert_eq!(
+        Params::from_dh_group_with_generators::<MODPGroup5>(
+            "brandskey".to_string(),
+            g.clone(),
+            p_minus_1,
+            g2.clone(),
+        )
+        .err(),
+        Some(Error::InvalidGenerator)
+    );
+    assert_eq!(
+        Params::from_dh_group_with_generators::<MODPGroup5>(
+            "brandskey".to_string(),
+            g.clone(),
+            g1,
+            BigUint::from(1u64),
+        )
+        .err(),
+        Some(Error::InvalidGenerator)
+    );
+    assert_eq!(
+        Params::from_dh_group_with_generators::<MODPGroup5>(
+            "brandskey".to_string(),
+            g.clone(),
+            g,
+            g2,
+        )
+        .err(),
+        Some(Error::DuplicateGenerators)
+    );
+}
+
+#[test]
+fn test_coin_string_form() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    let token = coin.to_string();
+    assert!(!token.contains('\n'));
+    let parsed: Coin = token.parse().unwrap();
+    assert!(parsed == coin);
+    assert!(parsed.verify(&issuer.h, &params));
+
+    // Corrupt a character in the middle of the token.
+    let mut corrupted = token.clone().into_bytes();
+    let index = corrupted.len() / 2;
+    corrupted[index] = if corrupted[index] == b'A' { b'B' } else { b'A' };
+    let corrupted = String::from_utf8(corrupted).unwrap();
+    assert_eq!(
+        corrupted.parse::<Coin>().err(),
+        Some(Error::InvalidChecksum)
+    );
+
+    // Unknown version prefix.
+    let other_version = token.replacen("-v1:", "-v0:", 1);
+    assert_eq!(
+        other_version.parse::<Coin>().err(),
+        Some(Error::UnsupportedVersion)
+    );
+}
+
+#[test]
+fn test_spend_once() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, mut spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+    let (other_coin, other_partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = spender
+        .spend_once(coin.clone(), partial_coin.clone(), &challenge)
+        .unwrap();
+    assert!(spent_coin.verify(&challenge, &params));
+
+    let challenge_2 = CoinChallenge::new("shopB-payment-item-1718193571".as_bytes(), &coin);
+    assert_eq!(
+        spender
+            .spend_once(coin.clone(), partial_coin, &challenge_2)
+            .err(),
+        Some(Error::AlreadySpent)
+    );
+
+    // Other coins are not affected.
+    let challenge_3 = CoinChallenge::new("shopB-payment-item-1718193571".as_bytes(), &other_coin);
+    assert!(spender
+        .spend_once(other_coin, other_partial_coin, &challenge_3)
+        .is_ok());
+}
+
+#[test]
+fn test_custom_coin_challenge() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    // Fold the merchant id into the challenge.
+    let merchant_id = "merchant-0042";
+    let challenge = CoinChallenge::new_with(&coin, &params, |coin| {
+        let digest = Sha256::new()
+            .chain_update(merchant_id)
+            .chain_update(coin.to_bytes())
+            .finalize();
+        BigUint::from_bytes_le(&digest)
+    })
+    .unwrap();
+    let spent_coin = spender.spend(coin.clone(), partial_coin, &challenge);
+    assert!(spent_coin.verify(&challenge, &params));
+
+    // The challenge must be less than q.
+    assert!(
+        CoinChallenge::new_with(&coin, &params, |_| MODPGroup5::sophie_garmain_prime()).is_err()
+    );
+}
+
+#[test]
+fn test_reveal_identities() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let issuer = Issuer::new(params.clone());
+    let mut spenders = Vec::new();
+    for _ in 0..3 {
+        let mut spender = Spender::new(params.clone());
+        spender.set_registration_id(issuer.register(&spender.i).unwrap());
+        spenders.push(spender);
+    }
+
+    let mut deposits = Vec::new();
+    let mut fingerprints = Vec::new();
+    for (n, spender) in spenders.iter().enumerate() {
+        let (coin, partial_coin) = withdraw_coin(&issuer, spender);
+        fingerprints.push(coin.fingerprint());
+        // The first two spenders double spend. The last one spends once, but the spent coin
+        // is deposited twice.
+        let challenge = CoinChallenge::new(format!("shopA-{n}").as_bytes(), &coin);
+        let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+        deposits.push(spent_coin.clone());
+        if n < 2 {
+            let challenge = CoinChallenge::new(format!("shopB-{n}").as_bytes(), &coin);
+            deposits.push(spender.spend(coin, partial_coin, &challenge));
+        } else {
+            deposits.push(spent_coin);
+        }
+    }
+
+    let revealed = issuer.reveal_identities(&deposits).unwrap();
+    assert_eq!(revealed.len(), 2);
+    assert_eq!(revealed[0], (fingerprints[0], spenders[0].i.clone()));
+    assert_eq!(revealed[1], (fingerprints[1], spenders[1].i.clone()));
+}
+
+#[test]
+fn test_clear_registration() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, mut spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+    assert!(coin.verify(&issuer.h, &params));
+
+    spender.clear_registration();
+    let (withdrawal_params, _) = issuer.setup_withdrawal_params(&spender.i);
+    assert_eq!(
+        spender.withdraw(withdrawal_params).err(),
+        Some(Error::NotRegistered)
+    );
+
+    // Register again.
+    spender.set_registration_id(issuer.register(&spender.i).unwrap());
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+    assert!(coin.verify(&issuer.h, &params));
+}
+
+#[test]
+fn test_identity_bound_coin_challenge() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let message = "shopA-payment-item-1718193570".as_bytes();
+    let challenge = CoinChallenge::new_bound(message, &coin, &spender.i);
+    let spent_coin = spender.spend(coin, partial_coin, &challenge);
+    assert!(spent_coin.verify_bound(message, &spender.i, &params));
+
+    let other_spender = Spender::new(params.clone());
+    assert!(!spent_coin.verify_bound(message, &other_spender.i, &params));
+}
+
+#[test]
+fn test_coin_metadata() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+
+    let metadata = CoinMetadata::new(100, "EUR".as_bytes().to_vec());
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender
+        .withdraw_with_metadata(withdrawal_params, metadata.clone())
+        .unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, _) = spender.make_coin(withdrawal, withdrawal_response);
+
+    assert!(coin.verify(&issuer.h, &params));
+    assert_eq!(
+        coin.verify_and_extract(&issuer.h, &params, &metadata),
+        Ok(metadata.clone())
+    );
+    let decoded = Coin::from_bytes(&coin.to_bytes()).unwrap();
+    assert_eq!(
+        decoded.verify_and_extract(&issuer.h, &params, &metadata),
+        Ok(metadata.clone())
+    );
+
+    // Claiming other metadata.
+    let tampered_metadata = CoinMetadata::new(1000, "EUR".as_bytes().to_vec());
+    assert_eq!(
+        coin.verify_and_extract(&issuer.h, &params, &tampered_metadata),
+        Err(VerifyError::MetadataMismatch)
+    );
+
+    // Altering the metadata in the coin.
+    let mut value = serde_json::to_value(&coin).unwrap();
+    value["metadata"]["denomination"] = serde_json::json!(1000);
+    let tampered_coin: Coin = serde_json::from_value(value).unwrap();
+    assert!(!tampered_coin.verify(&issuer.h, &params));
+    assert_eq!(
+        tampered_coin.verify_and_extract(&issuer.h, &params, &tampered_metadata),
+        Err(VerifyError::ChallengeMismatch)
+    );
+}
+
+#[test]
+fn test_spent_coin_ct_eq() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let coins: Vec<Coin> = (0..3).map(|_| withdraw_coin(&issuer, &spender).0).collect();
+
+    // Near-equal coins which differ from the withdrawn coins in one byte.
+    let mut candidates = coins.clone();
+    for (n, coin) in coins.iter().enumerate() {
+        let bytes = coin.to_bytes();
+        for index in (n..bytes.len()).step_by(37) {
+            let mut bytes = bytes.clone();
+            bytes[index] ^= 1 << (index % 8);
+            if let Ok(coin) = Coin::from_bytes(&bytes) {
+                candidates.push(coin);
+            }
+        }
+    }
+
+    let spent_coins: Vec<SpentCoin> = candidates
+        .iter()
+        .map(|coin| SpentCoin::from_parts(coin.clone(), BigUint::from(1u64), BigUint::from(2u64)))
+        .collect();
+    for a in &spent_coins {
+        for b in &spent_coins {
+            assert_eq!(bool::from(a.ct_eq(b)), a == b);
+            assert_eq!(bool::from(a.coin.ct_eq(&b.coin)), a.coin == b.coin);
+        }
+    }
+}
+
+#[test]
+fn test_deposit_ledger() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let mut ledger = DepositLedger::new(params.clone());
+
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+    assert_eq!(
+        ledger.deposit(spent_coin.clone(), challenge.clone()),
+        Ok(DepositOutcome::Accepted)
+    );
+    assert_eq!(
+        ledger.deposit(spent_coin, challenge.clone()),
+        Ok(DepositOutcome::Duplicate)
+    );
+
+    let challenge_2 = CoinChallenge::new("shopB-payment-item-1718193571".as_bytes(), &coin);
+    let spent_coin_2 = spender.spend(coin.clone(), partial_coin, &challenge_2);
+    let Ok(DepositOutcome::DoubleSpend(event)) = ledger.deposit(spent_coin_2, challenge_2.clone())
+    else {
+        panic!("double spending is not detected");
+    };
+    assert_eq!(event.fingerprint, coin.fingerprint());
+    assert_eq!(event.identity, spender.i);
+    assert_eq!(event.challenges, [challenge, challenge_2]);
+}
+
+#[test]
+fn test_restore_spender() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let issuer = Issuer::new(params.clone());
+
+    let u1 = BigUint::from(1234567890u64);
+    let mut spender = Spender::from_secret(params.clone(), u1.clone(), None).unwrap();
+    let z = issuer.register(&spender.i).unwrap();
+    spender.set_registration_id(z.clone());
+
+    // Restart the spender.
+    let restored = Spender::from_secret(params.clone(), u1, Some(z)).unwrap();
+    assert_eq!(restored.i, spender.i);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &restored);
+    assert!(coin.verify(&issuer.h, &params));
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = restored.spend(coin, partial_coin, &challenge);
+    assert!(spent_coin.verify(&challenge, &params));
+
+    let q = MODPGroup5::sophie_garmain_prime();
+    assert!(matches!(
+        Spender::from_secret(params.clone(), q, None),
+        Err(Error::InvalidSecret)
+    ));
+    assert!(matches!(
+        Spender::from_secret(params, BigUint::ZERO, None),
+        Err(Error::InvalidSecret)
+    ));
+}
+
+#[test]
+fn test_verify_rejects_invalid_issuer_key() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+    assert!(params.is_subgroup_element(&issuer.h));
+
+    // p - 1 has order 2, which is not in the subgroup of order q.
+    let h = MODPGroup5::prime_modulus() - 1u64;
+    assert!(!params.is_subgroup_element(&h));
+    assert!(!coin.verify(&h, &params));
+    let verifier = CoinVerifier::new(h, params.clone());
+    assert_eq!(verifier.verify(&coin), Err(VerifyError::InvalidIssuerKey));
+
+    let verifier = CoinVerifier::new(BigUint::from(1u64), params);
+    assert_eq!(verifier.verify(&coin), Err(VerifyError::InvalidIssuerKey));
+}
+
+#[test]
+fn test_k_show_coin() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw_k_show(withdrawal_params, 3).unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+    assert_eq!(coin.shows(), 3);
+    assert!(coin.verify(&issuer.h, &params));
+    assert!(Coin::from_bytes(&coin.to_bytes()).unwrap() == coin);
+
+    let mut ledger = DepositLedger::new(params.clone());
+    for (slot, message) in ["shopA", "shopB", "shopC"].iter().enumerate() {
+        let challenge = CoinChallenge::new(message.as_bytes(), &coin);
+        let spent_coin = spender
+            .spend_slot(coin.clone(), &partial_coin, slot as u32, &challenge)
+            .unwrap();
+        assert_eq!(spent_coin.slot(), slot as u32);
+        assert!(spent_coin.verify(&challenge, &params));
+        assert_eq!(
+            ledger.deposit(spent_coin, challenge),
+            Ok(DepositOutcome::Accepted)
+        );
+    }
+
+    let challenge = CoinChallenge::new("shopD".as_bytes(), &coin);
+    assert!(matches!(
+        spender.spend_slot(coin.clone(), &partial_coin, 3, &challenge),
+        Err(Error::InvalidSlot)
+    ));
+    let spent_coin = spender
+        .spend_slot(coin, &partial_coin, 0, &challenge)
+        .unwrap();
+    assert!(matches!(
+        ledger.deposit(spent_coin, challenge),
+        Ok(DepositOutcome::DoubleSpend(event)) if event.identity == spender.i
+    ));
+
+    let (withdrawal_params, _) = issuer.setup_withdrawal_params(&spender.i);
+    assert!(matches!(
+        spender.withdraw_k_show(withdrawal_params, 0),
+        Err(Error::InvalidShows)
+    ));
+}
+
+#[test]
+fn test_params_serde_decimal() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let serialized = serde_json::to_string(&params).unwrap();
+    let p = MODPGroup5::prime_modulus().to_string();
+    assert!(serialized.contains(&format!("\"p\":\"{}\"", p)));
+
+    let deserialized: Params = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(serde_json::to_string(&deserialized).unwrap(), serialized);
+
+    let mut value = serde_json::to_value(&params).unwrap();
+    value["p"] = serde_json::Value::String("not a number".to_string());
+    assert!(serde_json::from_value::<Params>(value).is_err());
+}
+
+#[test]
+fn test_spent_coin_responses() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin, &challenge);
+
+    // e.g. the scalars are handed to, and received from, another implementation.
+    let (r1, r2) = spent_coin.responses();
+    let (r1, r2) = (r1.to_string(), r2.to_string());
+    let rebuilt = SpentCoin::from_parts(coin, r1.parse().unwrap(), r2.parse().unwrap());
+    assert!(rebuilt.verify(&challenge, &params));
+    assert_eq!(rebuilt.responses(), spent_coin.responses());
+}
+
+#[test]
+fn test_spent_coin_bytes() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin, partial_coin, &challenge);
+    let bytes = spent_coin.to_bytes();
+    let decoded = SpentCoin::from_bytes(&bytes).unwrap();
+    assert!(decoded == spent_coin);
+    assert_eq!(decoded.responses(), spent_coin.responses());
+    assert!(decoded.verify(&challenge, &params));
+
+    assert_eq!(
+        SpentCoin::from_bytes(&bytes[..bytes.len() - 1]).err(),
+        Some(Error::DeserializationFailed)
+    );
+    assert_eq!(
+        SpentCoin::from_bytes(&[bytes.as_slice(), &[0]].concat()).err(),
+        Some(Error::DeserializationFailed)
+    );
+}
+
+#[test]
+fn test_reject_huge_length_prefix() {
+    // c1 declared as about 4GB long.
+    let mut bytes = (u32::MAX - 1).to_le_bytes().to_vec();
+    bytes.extend_from_slice(&[1u8; 64]);
+    assert_eq!(
+        Coin::from_bytes(&bytes).err(),
+        Some(Error::DeserializationFailed)
+    );
+
+    // c1 declared (and given) 4097 bytes long, which exceeds the cap.
+    let mut bytes = 4097u32.to_le_bytes().to_vec();
+    bytes.extend_from_slice(&[1u8; 4097]);
+    assert_eq!(
+        Coin::from_bytes(&bytes).err(),
+        Some(Error::DeserializationFailed)
+    );
+
+    // The same coin wrapped in a spent coin.
+    let mut spent_coin_bytes = (bytes.len() as u32).to_le_bytes().to_vec();
+    spent_coin_bytes.extend_from_slice(&bytes);
+    assert_eq!(
+        SpentCoin::from_bytes(&spent_coin_bytes).err(),
+        Some(Error::DeserializationFailed)
+    );
+}
+
+#[test]
+fn test_coin_challenge_persistence() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    let message = "shopA-payment-item-1718193570".as_bytes();
+    let challenge = CoinChallenge::new(message, &coin);
+    assert!(challenge == CoinChallenge::new(message, &coin));
+    assert!(challenge != CoinChallenge::new("shopB".as_bytes(), &coin));
+
+    let decoded = CoinChallenge::from_bytes(&challenge.to_bytes()).unwrap();
+    assert!(decoded == challenge);
+    let serialized = serde_json::to_string(&challenge).unwrap();
+    let deserialized: CoinChallenge = serde_json::from_str(&serialized).unwrap();
+    assert!(deserialized == challenge);
+
+    assert_eq!(
+        CoinChallenge::from_bytes(&[0, 0]).err(),
+        Some(Error::DeserializationFailed)
+    );
+}
+
+#[test]
+fn test_padded_bytes_fixed_length() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    assert_eq!(params.serialized_element_len(), 192);
+    assert_eq!(params.serialized_scalar_len(), 192);
+    let (issuer, spender) = setup_accounts(&params);
+
+    let coins: Vec<(Coin, PartialCoin)> =
+        (0..2).map(|_| withdraw_coin(&issuer, &spender)).collect();
+    let lengths: Vec<usize> = coins
+        .iter()
+        .map(|(coin, _)| coin.to_padded_bytes(&params).len())
+        .collect();
+    assert_eq!(lengths[0], lengths[1]);
+
+    let spent_coins: Vec<SpentCoin> = coins
+        .into_iter()
+        .map(|(coin, partial_coin)| {
+            let challenge = CoinChallenge::new("shopA".as_bytes(), &coin);
+            spender.spend(coin, partial_coin, &challenge)
+        })
+        .collect();
+    let bytes: Vec<Vec<u8>> = spent_coins
+        .iter()
+        .map(|spent_coin| spent_coin.to_padded_bytes(&params))
+        .collect();
+    assert_eq!(bytes[0].len(), bytes[1].len());
+
+    // The padded encodings are decoded as the minimal ones.
+    let decoded = SpentCoin::from_bytes(&bytes[0]).unwrap();
+    assert!(decoded.coin.to_bytes() == spent_coins[0].coin.to_bytes());
+    assert_eq!(decoded.responses(), spent_coins[0].responses());
+}
+
+#[test]
+fn test_deposit_receipt() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender
+        .withdraw_with_metadata(withdrawal_params, CoinMetadata::new(100, vec![]))
+        .unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin, &challenge);
+    let receipt = issuer.issue_receipt(&spent_coin, &challenge).unwrap();
+    assert_eq!(receipt.fingerprint, coin.fingerprint());
+    assert_eq!(receipt.amount, 100);
+    assert!(receipt.verify(&issuer.h, &params));
+
+    let serialized = serde_json::to_string(&receipt).unwrap();
+    let deserialized: Receipt = serde_json::from_str(&serialized).unwrap();
+    assert!(deserialized.verify(&issuer.h, &params));
+
+    let mut tampered = receipt.clone();
+    tampered.fingerprint.0[0] ^= 1;
+    assert!(!tampered.verify(&issuer.h, &params));
+    let mut tampered = receipt.clone();
+    tampered.amount = 1000;
+    assert!(!tampered.verify(&issuer.h, &params));
+    let other_issuer = Issuer::new(params.clone());
+    assert!(!receipt.verify(&other_issuer.h, &params));
+
+    let challenge_2 = CoinChallenge::new("shopB".as_bytes(), &coin);
+    assert_eq!(
+        issuer.issue_receipt(&spent_coin, &challenge_2),
+        Err(Error::InvalidSpentCoin)
+    );
+}
+
+#[test]
+fn test_reject_withdrawal_nonce_reuse() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (_, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+    assert!(issuer
+        .withdrawal_response(withdrawal_response_params.clone(), &withdrawal_challenge)
+        .is_ok());
+    assert!(matches!(
+        issuer.withdrawal_response(withdrawal_response_params, &withdrawal_challenge),
+        Err(Error::NonceReuse)
+    ));
+
+    // Fresh parameters are accepted.
+    withdraw_coin(&issuer, &spender);
+}
+
+#[test]
+fn test_resume_withdrawal() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+
+    // The spender persists the withdrawal while waiting for the response of the issuer.
+    let serialized = serde_json::to_string(&withdrawal).unwrap();
+    let resumed: Withdrawal = serde_json::from_str(&serialized).unwrap();
+    assert!(resumed == withdrawal);
+    let serialized = serde_json::to_string(&withdrawal_challenge).unwrap();
+    let withdrawal_challenge: WithdrawalChallenge = serde_json::from_str(&serialized).unwrap();
+
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let serialized = serde_json::to_string(&withdrawal_response).unwrap();
+    let deserialized: WithdrawalResponse = serde_json::from_str(&serialized).unwrap();
+    assert!(deserialized == withdrawal_response);
+
+    let (coin, partial_coin) = spender.make_coin(resumed, withdrawal_response);
+    assert!(coin.verify(&issuer.h, &params));
+    assert!(partial_coin == PartialCoin::from(withdrawal));
+}
+
+#[test]
+fn test_precomputed_verifier() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let verifier = CoinVerifier::new(issuer.h.clone(), params.clone());
+    let precomputed = PrecomputedVerifier::new(issuer.h.clone(), params.clone()).unwrap();
+
+    for _ in 0..3 {
+        let (coin, _) = withdraw_coin(&issuer, &spender);
+        assert_eq!(precomputed.verify(&coin), Ok(()));
+
+        let mut value = serde_json::to_value(&coin).unwrap();
+        let limb = value["c6"][0].as_u64().unwrap();
+        value["c6"][0] = serde_json::json!(limb ^ 1);
+        let tampered_coin: Coin = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            precomputed.verify(&tampered_coin),
+            Err(VerifyError::SignatureEqnFailed)
+        );
+        assert_eq!(
+            precomputed.verify(&tampered_coin),
+            verifier.verify(&tampered_coin)
+        );
+    }
+
+    let other_issuer = Issuer::new(params.clone());
+    let (coin, _) = withdraw_coin(&other_issuer, &spender);
+    let precomputed = PrecomputedVerifier::new(issuer.h.clone(), params.clone()).unwrap();
+    assert_eq!(
+        precomputed.verify(&coin),
+        Err(VerifyError::SignatureEqnFailed)
+    );
+
+    assert!(matches!(
+        PrecomputedVerifier::new(BigUint::from(1u64), params),
+        Err(VerifyError::InvalidIssuerKey)
+    ));
+}
+
+#[test]
+fn test_setup_withdrawal_params_many() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+
+    let aggregated =
+        issuer.setup_withdrawal_params_many(&spender.i, 3, &mut StdRng::seed_from_u64(42));
+    let mut rng = StdRng::seed_from_u64(42);
+    let individual: Vec<_> = (0..3)
+        .map(|_| {
+            issuer
+                .setup_withdrawal_params_many(&spender.i, 1, &mut rng)
+                .remove(0)
+        })
+        .collect();
+    assert_eq!(aggregated.len(), 3);
+    for ((params_1, _), (params_2, _)) in aggregated.iter().zip(&individual) {
+        assert!(params_1 == params_2);
+    }
+    assert!(aggregated[0].0 != aggregated[1].0);
+
+    for (withdrawal_params, withdrawal_response_params) in aggregated {
+        let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+        let withdrawal_response = issuer
+            .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+            .unwrap();
+        let (coin, _) = spender.make_coin(withdrawal, withdrawal_response);
+        assert!(coin.verify(&issuer.h, &params));
+    }
+}
+
+#[test]
+fn test_dyn_verify_coin() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer_1, spender) = setup_accounts(&params);
+    let issuer_2 = Issuer::new(params.clone());
+    let mut spender_2 = Spender::new(params.clone());
+    spender_2.set_registration_id(issuer_2.register(&spender_2.i).unwrap());
+
+    // Routes the coins to the verifiers by the issuer ids.
+    let verifiers: Vec<Box<dyn VerifyCoin>> = vec![
+        Box::new(CoinVerifier::new(issuer_1.h.clone(), params.clone())),
+        Box::new(PrecomputedVerifier::new(issuer_2.h.clone(), params.clone()).unwrap()),
+    ];
+    let coins = [
+        withdraw_coin(&issuer_1, &spender).0,
+        withdraw_coin(&issuer_2, &spender_2).0,
+    ];
+    for (issuer_id, coin) in coins.iter().enumerate() {
+        let bytes = coin.to_bytes();
+        assert_eq!(verifiers[issuer_id].verify(&bytes), Ok(()));
+        assert_eq!(
+            verifiers[1 - issuer_id].verify(&bytes),
+            Err(VerifyError::SignatureEqnFailed)
+        );
+        assert_eq!(
+            verifiers[issuer_id].verify(&bytes[1..]),
+            Err(VerifyError::MalformedCoin)
+        );
+    }
+}
+
+#[test]
+fn test_verify_rejects_degenerate_coins() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+    let verifier = CoinVerifier::new(issuer.h.clone(), params.clone());
+    assert_eq!(verifier.verify(&coin), Ok(()));
+
+    for field in ["c1", "c2", "c3"] {
+        let mut value = serde_json::to_value(&coin).unwrap();
+        value[field] = serde_json::json!([1]);
+        let degenerate_coin: Coin = serde_json::from_value(value).unwrap();
+        assert!(!degenerate_coin.verify(&issuer.h, &params));
+        assert_eq!(
+            verifier.verify(&degenerate_coin),
+            Err(VerifyError::DegenerateCoin)
+        );
+    }
+}
+
+#[test]
+fn test_tenant_isolation() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let params_a = params.clone().with_tenant_id(b"tenant-a");
+    let params_b = params.clone().with_tenant_id(b"tenant-b");
+    assert_eq!(params_a.tenant_id(), b"tenant-a");
+    assert!(params.tenant_id().is_empty());
+
+    let (issuer, spender) = setup_accounts(&params_a);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+    assert!(coin.verify(&issuer.h, &params_a));
+    assert_eq!(
+        CoinVerifier::new(issuer.h.clone(), params_b).verify(&coin),
+        Err(VerifyError::ChallengeMismatch)
+    );
+    assert_eq!(
+        CoinVerifier::new(issuer.h.clone(), params.clone()).verify(&coin),
+        Err(VerifyError::ChallengeMismatch)
+    );
+
+    // The tenant id is kept in the serialized parameters.
+    let serialized = serde_json::to_string(&params_a).unwrap();
+    let deserialized: Params = serde_json::from_str(&serialized).unwrap();
+    assert!(coin.verify(&issuer.h, &deserialized));
+}
+
+#[test]
+fn test_coin_serialized_len() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+
+    let mut coins: Vec<Coin> = (0..5).map(|_| withdraw_coin(&issuer, &spender).0).collect();
+    for (metadata, shows) in [
+        (Some(CoinMetadata::new(100, b"EUR".to_vec())), 1),
+        (None, 3),
+    ] {
+        let (withdrawal_params, withdrawal_response_params) =
+            issuer.setup_withdrawal_params(&spender.i);
+        let (withdrawal, withdrawal_challenge) = match metadata {
+            Some(metadata) => spender.withdraw_with_metadata(withdrawal_params, metadata),
+            None => spender.withdraw_k_show(withdrawal_params, shows),
+        }
+        .unwrap();
+        let withdrawal_response = issuer
+            .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+            .unwrap();
+        coins.push(spender.make_coin(withdrawal, withdrawal_response).0);
+    }
+
+    for coin in coins {
+        assert_eq!(coin.serialized_len(), coin.to_bytes().len());
+    }
+}
+
+#[test]
+fn test_coin_format_version() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+    assert_eq!(coin.format_version(&params), CoinFormat::V2);
+
+    // The same coin re-encoded with cd + q (< p) is in the legacy range of cd, but it carries
+    // neither the current challenge nor the legacy one.
+    let [c1, c2, c3, c4, c5, c6, cd] = coin.fields().map(Clone::clone);
+    let shifted_coin = Coin::from_fields([
+        c1,
+        c2,
+        c3,
+        c4,
+        c5,
+        c6,
+        cd + MODPGroup5::sophie_garmain_prime(),
+    ]);
+    assert_eq!(shifted_coin.format_version(&params), CoinFormat::V1);
+    assert_eq!(
+        shifted_coin.verify_format(&issuer.h, &params, CoinFormat::V1),
+        Err(VerifyError::ChallengeMismatch)
+    );
+    assert_eq!(
+        shifted_coin.verify_format(&issuer.h, &params, CoinFormat::V2),
+        Err(VerifyError::LegacyFormat)
+    );
+    assert!(!shifted_coin.verify(&issuer.h, &params));
+    assert_eq!(
+        coin.verify_format(&issuer.h, &params, CoinFormat::V1),
+        Ok(())
+    );
+    assert_eq!(
+        coin.verify_format(&issuer.h, &params, CoinFormat::V2),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_deposit_shifted_challenge() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    // The coin is spent once as is, and once re-encoded with cd + q.
+    let [c1, c2, c3, c4, c5, c6, cd] = coin.fields().map(Clone::clone);
+    let shifted_coin = Coin::from_fields([
+        c1,
+        c2,
+        c3,
+        c4,
+        c5,
+        c6,
+        cd + MODPGroup5::sophie_garmain_prime(),
+    ]);
+    assert!(shifted_coin.fingerprint() != coin.fingerprint());
+    let challenge = CoinChallenge::new("shopA".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin, partial_coin.clone(), &challenge);
+    let shifted_challenge = CoinChallenge::new("shopB".as_bytes(), &shifted_coin);
+    let shifted_spent_coin = spender.spend(shifted_coin, partial_coin, &shifted_challenge);
+    assert!(shifted_spent_coin.verify(&shifted_challenge, &params));
+
+    let mut ledger = DepositLedger::new(params.clone());
+    let concurrent_ledger = ConcurrentDepositLedger::new(params.clone());
+    assert_eq!(
+        ledger.deposit(spent_coin.clone(), challenge.clone()),
+        Ok(DepositOutcome::Accepted)
+    );
+    assert_eq!(
+        concurrent_ledger.deposit(spent_coin, challenge),
+        Ok(DepositOutcome::Accepted)
+    );
+    for outcome in [
+        ledger.deposit(shifted_spent_coin.clone(), shifted_challenge.clone()),
+        concurrent_ledger.deposit(shifted_spent_coin, shifted_challenge),
+    ] {
+        match outcome {
+            Ok(DepositOutcome::DoubleSpend(event)) => assert_eq!(event.identity, spender.i),
+            _ => panic!("the shifted coin is not detected as double spent"),
+        }
+    }
+}
+
+#[test]
+fn test_coin_format_legacy_challenge() {
+    use hmac::{Hmac, Mac};
+
+    let p = MODPGroup5::prime_modulus();
+    let q = MODPGroup5::sophie_garmain_prime();
+    let params = Params::from_str(
+        "brandskey".to_string(),
+        &p.to_string(),
+        &q.to_string(),
+        "4",
+        "9",
+        "25",
+    )
+    .unwrap();
+    let x = BigUint::from(0x5eed_1234_u64);
+    let issuer = Issuer::from_secret(params.clone(), x.clone()).unwrap();
+
+    // A coin signed as before the coin challenge was hashed into a scalar, i.e.
+    // cd = HMac(scheme_key, c1 || c2 || c3 || c4 || c5) mod p.
+    let g = BigUint::from(4u64);
+    let c1 = BigUint::from(49u64).modpow(&BigUint::from(7u64), &p);
+    let c2 = BigUint::from(121u64).modpow(&BigUint::from(3u64), &p);
+    let c3 = c1.modpow(&x, &p);
+    let w = BigUint::from(0xabcdef_u64);
+    let c4 = g.modpow(&w, &p);
+    let c5 = c1.modpow(&w, &p);
+    let mut mac = Hmac::<Sha256>::new_from_slice(b"brandskey").unwrap();
+    for field in [&c1, &c2, &c3, &c4, &c5] {
+        mac.update(&field.to_bytes_le());
+    }
+    let cd = BigUint::from_bytes_le(&mac.finalize().into_bytes()) % &p;
+    let c6 = (&w + &cd * &x) % &q;
+
+    let mut bytes = Vec::new();
+    for field in [&c1, &c2, &c3, &c4, &c5, &c6, &cd] {
+        let field_bytes = field.to_bytes_le();
+        bytes.extend_from_slice(&(field_bytes.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&field_bytes);
+    }
+    let legacy_coin = Coin::from_bytes(&bytes).unwrap();
+    assert_eq!(legacy_coin.format_version(&params), CoinFormat::V1);
+    assert_eq!(
+        legacy_coin.verify_format(&issuer.h, &params, CoinFormat::V1),
+        Ok(())
+    );
+    assert_eq!(
+        legacy_coin.verify_format(&issuer.h, &params, CoinFormat::V2),
+        Err(VerifyError::LegacyFormat)
+    );
+    assert!(!legacy_coin.verify(&issuer.h, &params));
+
+    // The legacy challenge is bound to the fields as well.
+    let mut tampered_bytes = Vec::new();
+    for field in [&c1, &c3, &c3, &c4, &c5, &c6, &cd] {
+        let field_bytes = field.to_bytes_le();
+        tampered_bytes.extend_from_slice(&(field_bytes.len() as u32).to_le_bytes());
+        tampered_bytes.extend_from_slice(&field_bytes);
+    }
+    let tampered_coin = Coin::from_bytes(&tampered_bytes).unwrap();
+    assert_eq!(
+        tampered_coin.verify_format(&issuer.h, &params, CoinFormat::V1),
+        Err(VerifyError::ChallengeMismatch)
+    );
+}
+
+#[test]
+fn test_withdrawal_session() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let session = WithdrawalSession::new(&spender, withdrawal_params);
+    let (session, withdrawal_challenge) = session.challenge().unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let coin_with_secrets = session.finalize(withdrawal_response);
+    assert!(coin_with_secrets.coin.verify(&issuer.h, &params));
+
+    let challenge = CoinChallenge::new("shopA".as_bytes(), &coin_with_secrets.coin);
+    let spent_coin = spender.spend_with_secrets(coin_with_secrets, &challenge);
+    assert!(spent_coin.verify(&challenge, &params));
+}
+
+#[test]
+fn test_withdrawal_response_checked() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (other_withdrawal_params, _) = issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params.clone()).unwrap();
+
+    // The parameters of another withdrawal.
+    assert!(matches!(
+        issuer.withdrawal_response_checked(
+            &other_withdrawal_params,
+            withdrawal_response_params.clone(),
+            &withdrawal_challenge
+        ),
+        Err(Error::WithdrawalMismatch)
+    ));
+
+    // A challenge out of range.
+    let mut value = serde_json::to_value(&withdrawal_challenge).unwrap();
+    value["c"] = serde_json::to_value(MODPGroup5::sophie_garmain_prime()).unwrap();
+    let invalid_challenge: WithdrawalChallenge = serde_json::from_value(value).unwrap();
+    assert!(matches!(
+        issuer.withdrawal_response_checked(
+            &withdrawal_params,
+            withdrawal_response_params.clone(),
+            &invalid_challenge
+        ),
+        Err(Error::InvalidChallenge)
+    ));
+
+    let withdrawal_response = issuer
+        .withdrawal_response_checked(
+            &withdrawal_params,
+            withdrawal_response_params,
+            &withdrawal_challenge,
+        )
+        .unwrap();
+    let (coin, _) = spender.make_coin(withdrawal, withdrawal_response);
+    assert!(coin.verify(&issuer.h, &params));
+}
+
+#[test]
+fn test_deterministic_withdrawal() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let issuer = Issuer::new(params.clone());
+    let seed = b"wallet seed";
+
+    let mut spender = Spender::new_deterministic(params.clone(), seed);
+    let registration_id = issuer.register(&spender.i).unwrap();
+    spender.set_registration_id(registration_id.clone());
+
+    // The spender is recovered from the same seed.
+    let mut recovered = Spender::new_deterministic(params.clone(), seed);
+    assert_eq!(recovered.i, spender.i);
+    recovered.set_registration_id(registration_id);
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender
+        .withdraw_deterministic(withdrawal_params.clone(), 7)
+        .unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let withdrawal_response_copy: WithdrawalResponse =
+        serde_json::from_str(&serde_json::to_string(&withdrawal_response).unwrap()).unwrap();
+
+    let (recovered_withdrawal, recovered_challenge) = recovered
+        .withdraw_deterministic(withdrawal_params.clone(), 7)
+        .unwrap();
+    assert!(recovered_withdrawal == withdrawal);
+    assert!(recovered_challenge == withdrawal_challenge);
+
+    // A different counter derives a different coin.
+    let (other_withdrawal, _) = spender
+        .withdraw_deterministic(withdrawal_params.clone(), 8)
+        .unwrap();
+    assert!(other_withdrawal != withdrawal);
+
+    let (coin, _) = spender.make_coin(withdrawal, withdrawal_response);
+    assert!(coin.verify(&issuer.h, &params));
+    let (recovered_coin, _) = recovered.make_coin(recovered_withdrawal, withdrawal_response_copy);
+    assert!(recovered_coin == coin);
+
+    // The randomly instantiated spender cannot withdraw deterministically.
+    let (_, spender) = setup_accounts(&params);
+    assert!(matches!(
+        spender.withdraw_deterministic(withdrawal_params, 7),
+        Err(Error::NotDeterministic)
+    ));
+}
+
+#[test]
+fn test_identity_bytes() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (_, spender) = setup_accounts(&params);
+
+    let bytes = identity_to_bytes(&spender.i, &params);
+    assert_eq!(bytes.len(), params.serialized_element_len());
+    assert_eq!(identity_from_bytes(&bytes, &params).unwrap(), spender.i);
+
+    // p - 1 is not an element of the subgroup of order q.
+    let p = MODPGroup5::prime_modulus();
+    let bytes = identity_to_bytes(&(p - 1u64), &params);
+    assert!(matches!(
+        identity_from_bytes(&bytes, &params),
+        Err(Error::InvalidIdentity)
+    ));
+
+    assert!(matches!(
+        identity_from_bytes(&bytes[1..], &params),
+        Err(Error::DeserializationFailed)
+    ));
+}
+
+#[test]
+fn test_verify_with_public_params_only() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    assert_eq!(params.scheme_key(), "brandskey");
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    // The verifier receives only the identity of the issuer and the published parameters,
+    // which carry the scheme key.
+    let published = serde_json::to_string(&CoinVerifier::new(issuer.h.clone(), params)).unwrap();
+    let verifier: CoinVerifier = serde_json::from_str(&published).unwrap();
+    assert_eq!(verifier.params.scheme_key(), "brandskey");
+    assert!(verifier.verify(&coin).is_ok());
+}
+
+#[test]
+fn test_params_bytes() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string()).with_tenant_id(b"t");
+    let bytes = params.to_bytes();
+    let decoded = Params::try_from(bytes.as_slice()).unwrap();
+    assert_eq!(decoded.to_bytes(), bytes);
+    assert_eq!(decoded.scheme_key(), "brandskey");
+    assert_eq!(decoded.tenant_id(), b"t");
+
+    assert!(matches!(
+        Params::try_from(&bytes[..bytes.len() - 1]),
+        Err(Error::DeserializationFailed)
+    ));
+
+    let p = MODPGroup5::prime_modulus();
+    let q = MODPGroup5::sophie_garmain_prime();
+    let from_str = |p: &BigUint, g: &str| {
+        Params::from_str(
+            "brandskey".to_string(),
+            &p.to_string(),
+            &q.to_string(),
+            g,
+            "9",
+            "25",
+        )
+        .unwrap()
+    };
+    // p != 2q + 1
+    let invalid = from_str(&(&p + 2u64), "4");
+    assert!(matches!(
+        Params::try_from(invalid.to_bytes().as_slice()),
+        Err(Error::InvalidParams)
+    ));
+    let invalid = from_str(&p, "1");
+    assert!(matches!(
+        Params::try_from(invalid.to_bytes().as_slice()),
+        Err(Error::InvalidGenerator)
+    ));
+    assert!(Params::try_from(from_str(&p, "4").to_bytes().as_slice()).is_ok());
+}
+
+#[test]
+fn test_concurrent_deposit_ledger() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let deposits: Vec<(SpentCoin, CoinChallenge)> = ["shopA", "shopB"]
+        .iter()
+        .map(|message| {
+            let challenge = CoinChallenge::new(message.as_bytes(), &coin);
+            let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+            (spent_coin, challenge)
+        })
+        .collect();
+
+    let ledger = ConcurrentDepositLedger::new(params.clone());
+    let outcomes: Vec<DepositOutcome> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..16)
+            .map(|n| {
+                let (spent_coin, challenge) = deposits[n % 2].clone();
+                let ledger = &ledger;
+                scope.spawn(move || ledger.deposit(spent_coin, challenge).unwrap())
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let count = |f: fn(&DepositOutcome) -> bool| outcomes.iter().filter(|o| f(o)).count();
+    assert_eq!(count(|o| matches!(o, DepositOutcome::Accepted)), 1);
+    assert_eq!(count(|o| matches!(o, DepositOutcome::Duplicate)), 14);
+    let events: Vec<_> = outcomes
+        .iter()
+        .filter_map(|o| match o {
+            DepositOutcome::DoubleSpend(event) => Some(event),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].identity, spender.i);
+}
+
+#[test]
+fn test_denominated_deposit() {
+    enum Eur {}
+    impl Unit for Eur {
+        const CODE: &'static [u8] = b"EUR";
+    }
+    enum Usd {}
+    impl Unit for Usd {
+        const CODE: &'static [u8] = b"USD";
+    }
+
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+
+    let denomination = Denomination::<Eur>::new(5);
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender
+        .withdraw_with_metadata(withdrawal_params, denomination.into())
+        .unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+    assert!(coin
+        .verify_and_extract(&issuer.h, &params, &denomination.into())
+        .is_ok());
+
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin, partial_coin, &challenge);
+
+    // The unit is checked when the spent coin is tagged.
+    assert!(matches!(
+        DenominatedSpentCoin::<Usd>::new(spent_coin.clone()),
+        Err(Error::UnitMismatch)
+    ));
+    let spent_coin = DenominatedSpentCoin::<Eur>::new(spent_coin).unwrap();
+    assert_eq!(spent_coin.denomination(), denomination);
+    assert_eq!(spent_coin.denomination().amount(), 5);
+
+    let mut ledger = DenominatedLedger::<Eur>::new(params);
+    assert_eq!(
+        ledger.deposit(spent_coin, challenge),
+        Ok(DepositOutcome::Accepted)
+    );
+}
+
+#[test]
+fn test_refresh_coin() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (old_coin, old_partial_coin) = withdraw_coin(&issuer, &spender);
+    let mut ledger = DepositLedger::new(params.clone());
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, request) = spender
+        .request_refresh(
+            old_coin.clone(),
+            old_partial_coin.clone(),
+            withdrawal_params,
+        )
+        .unwrap();
+    let withdrawal_response = issuer
+        .refresh(&request, &mut ledger, withdrawal_response_params)
+        .unwrap();
+    let (new_coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+    assert!(new_coin.verify(&issuer.h, &params));
+
+    // The old coin cannot be refreshed again.
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (_, request) = spender
+        .request_refresh(
+            old_coin.clone(),
+            old_partial_coin.clone(),
+            withdrawal_params,
+        )
+        .unwrap();
+    assert!(matches!(
+        issuer.refresh(&request, &mut ledger, withdrawal_response_params),
+        Err(Error::AlreadySpent)
+    ));
+
+    // Spending the old coin is detected as double spending.
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &old_coin);
+    let spent_coin = spender.spend(old_coin, old_partial_coin, &challenge);
+    match ledger.deposit(spent_coin, challenge) {
+        Ok(DepositOutcome::DoubleSpend(event)) => assert_eq!(event.identity, spender.i),
+        _ => panic!("the old coin is not detected as spent"),
+    }
+
+    // The new coin spends cleanly.
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &new_coin);
+    let spent_coin = spender.spend(new_coin, partial_coin, &challenge);
+    assert!(spent_coin.verify(&challenge, &params));
+    assert_eq!(
+        ledger.deposit(spent_coin, challenge),
+        Ok(DepositOutcome::Accepted)
+    );
+}
+
+#[test]
+fn test_refresh_nonce_reuse() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (old_coin, old_partial_coin) = withdraw_coin(&issuer, &spender);
+    let mut ledger = DepositLedger::new(params.clone());
+
+    // The withdrawal parameters have been responded to in another withdrawal.
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (_, withdrawal_challenge) = spender.withdraw(withdrawal_params.clone()).unwrap();
+    issuer
+        .withdrawal_response(withdrawal_response_params.clone(), &withdrawal_challenge)
+        .unwrap();
+
+    let (_, request) = spender
+        .request_refresh(
+            old_coin.clone(),
+            old_partial_coin.clone(),
+            withdrawal_params,
+        )
+        .unwrap();
+    assert!(matches!(
+        issuer.refresh(&request, &mut ledger, withdrawal_response_params),
+        Err(Error::NonceReuse)
+    ));
+
+    // The old coin is not deposited, so it can still be refreshed or spent.
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, request) = spender
+        .request_refresh(old_coin, old_partial_coin, withdrawal_params)
+        .unwrap();
+    let withdrawal_response = issuer
+        .refresh(&request, &mut ledger, withdrawal_response_params)
+        .unwrap();
+    let (new_coin, _) = spender.make_coin(withdrawal, withdrawal_response);
+    assert!(new_coin.verify(&issuer.h, &params));
+}
+
+#[test]
+fn test_verify_batch_detailed() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+    let (other_coin, _) = withdraw_coin(&issuer, &spender);
+
+    // The coin issued by another issuer.
+    let (other_issuer, other_spender) = setup_accounts(&params);
+    let (foreign_coin, _) = withdraw_coin(&other_issuer, &other_spender);
+
+    // The coin whose c4 is replaced.
+    let mut value = serde_json::to_value(&coin).unwrap();
+    value["c4"] = serde_json::to_value(&other_coin).unwrap()["c4"].clone();
+    let tampered_coin: Coin = serde_json::from_value(value).unwrap();
+
+    let coins = [coin, foreign_coin, tampered_coin, other_coin];
+    assert_eq!(
+        Coin::verify_batch_detailed(&coins, &issuer.h, &params),
+        vec![
+            Ok(()),
+            Err(VerifyError::SignatureEqnFailed),
+            Err(VerifyError::ChallengeMismatch),
+            Ok(())
+        ]
+    );
+    assert_eq!(
+        Coin::verify_batch(&coins, &issuer.h, &params),
+        vec![true, false, false, true]
+    );
+}
+
+#[test]
+fn test_coin_challenge_from_reader() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    for message in [
+        "shopA-payment-item-1718193570".as_bytes().to_vec(),
+        "invoice line\n".repeat(10_000).into_bytes(),
+    ] {
+        let challenge = CoinChallenge::new_from_reader(message.as_slice(), &coin).unwrap();
+        assert_eq!(challenge, CoinChallenge::new(&message, &coin));
+    }
+
+    let invoice = "invoice line\n".repeat(10_000);
+    let challenge = CoinChallenge::new_from_reader(invoice.as_bytes(), &coin).unwrap();
+    let spent_coin = spender.spend(coin, partial_coin, &challenge);
+    assert!(spent_coin.verify(&challenge, &params));
+}
+
+#[test]
+fn test_issuer_from_secret() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let x = BigUint::from(987654321u64);
+    let issuer = Issuer::from_secret(params.clone(), x.clone()).unwrap();
+    let mut spender = Spender::new(params.clone());
+    spender.set_registration_id(issuer.register(&spender.i).unwrap());
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    // Restart the issuer.
+    let restored = Issuer::from_secret(params.clone(), x).unwrap();
+    assert_eq!(restored.h, issuer.h);
+    assert_eq!(restored.register(&spender.i), issuer.register(&spender.i));
+    assert!(coin.verify(&restored.h, &params));
+    let (coin, _) = withdraw_coin(&restored, &spender);
+    assert!(coin.verify(&issuer.h, &params));
+
+    let q = MODPGroup5::sophie_garmain_prime();
+    assert!(matches!(
+        Issuer::from_secret(params.clone(), q),
+        Err(Error::InvalidSecret)
+    ));
+    assert!(matches!(
+        Issuer::from_secret(params, BigUint::ZERO),
+        Err(Error::InvalidSecret)
+    ));
+}
+
+#[test]
+fn test_sign_transaction() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let transaction = b"pay 5 EUR to shopA for item-1718193570";
+    let onetime_pk = spender.onetime_public_key(&partial_coin);
+    let challenge = CoinChallenge::new_with_onetime_key(transaction, &coin, &onetime_pk);
+    let signature = spender.sign_transaction(&partial_coin, transaction);
+    let spent_coin = spender.spend(coin, partial_coin, &challenge);
+
+    assert!(spent_coin.verify(&challenge, &params));
+    assert!(signature.verify(&onetime_pk, transaction, &params));
+    assert!(!signature.verify(&onetime_pk, b"pay 50 EUR to shopA", &params));
+    assert!(!signature.verify(&issuer.h, transaction, &params));
+}
+
+#[test]
+fn test_spent_coin_verify_full() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let challenge = CoinChallenge::new("shopA-payment-item-1718193570".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+    assert_eq!(
+        spent_coin.verify_full(&issuer.h, &challenge, &params),
+        Ok(())
+    );
+
+    let challenge_2 = CoinChallenge::new("shopB-payment-item-1718193571".as_bytes(), &coin);
+    assert_eq!(
+        spent_coin.verify_full(&issuer.h, &challenge_2, &params),
+        Err(VerifyError::ResponseEqnFailed)
+    );
+
+    // The responses are valid, but the coin (i.e. c6) is not signed by the issuer.
+    let mut value = serde_json::to_value(&coin).unwrap();
+    value["c6"] = serde_json::to_value(BigUint::from(1u64)).unwrap();
+    let forged_coin: Coin = serde_json::from_value(value).unwrap();
+    let spent_coin = spender.spend(forged_coin, partial_coin, &challenge);
+    assert!(spent_coin.verify(&challenge, &params));
+    assert_eq!(
+        spent_coin.verify_full(&issuer.h, &challenge, &params),
+        Err(VerifyError::SignatureEqnFailed)
+    );
+}
+
+proptest::proptest! {
+    #[test]
+    fn test_reveal_identity_never_panics(
+        r1 in proptest::collection::vec(proptest::num::u8::ANY, 0..200),
+        r2 in proptest::collection::vec(proptest::num::u8::ANY, 0..200),
+        r1_2 in proptest::collection::vec(proptest::num::u8::ANY, 0..200),
+        r2_2 in proptest::collection::vec(proptest::num::u8::ANY, 0..200),
+    ) {
+        static COIN: OnceLock<(Params, Coin)> = OnceLock::new();
+        let (params, coin) = COIN.get_or_init(|| {
+            let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+            let (issuer, spender) = setup_accounts(&params);
+            let (coin, _) = withdraw_coin(&issuer, &spender);
+            (params, coin)
+        });
+
+        let spent_coin =
+            SpentCoin::from_parts(coin.clone(), BigUint::from_bytes_le(&r1), BigUint::from_bytes_le(&r2));
+        let spent_coin_2 = SpentCoin::from_parts(
+            coin.clone(),
+            BigUint::from_bytes_le(&r1_2),
+            BigUint::from_bytes_le(&r2_2),
+        );
+        let result = spent_coin.reveal_identity(&spent_coin_2, params);
+        let q = MODPGroup5::sophie_garmain_prime();
+        if BigUint::from_bytes_le(&r2) % &q == BigUint::from_bytes_le(&r2_2) % &q {
+            proptest::prop_assert_eq!(result, Err(Error::SameChallenge));
+        } else {
+            proptest::prop_assert!(result.is_ok());
+        }
+    }
+}
+
+#[test]
+fn test_coin_challenge_from_txid() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let txid = [7u8; 32];
+    let challenge = CoinChallenge::from_txid(&txid, &coin);
+    assert_eq!(CoinChallenge::from_txid(&txid, &coin), challenge);
+    assert_ne!(CoinChallenge::from_txid(&[8u8; 32], &coin), challenge);
+    assert_ne!(CoinChallenge::new(&txid, &coin), challenge);
+
+    // Retrying the payment is a duplicate rather than a double spending.
+    let mut ledger = DepositLedger::new(params.clone());
+    for outcome in [DepositOutcome::Accepted, DepositOutcome::Duplicate] {
+        let challenge = CoinChallenge::from_txid(&txid, &coin);
+        let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+        assert!(spent_coin.verify(&challenge, &params));
+        assert_eq!(ledger.deposit(spent_coin, challenge), Ok(outcome));
+    }
+}
+
+#[test]
+fn test_verify_serialized_coin() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    let issuer_public_bytes = CoinVerifier::new(issuer.h.clone(), params.clone()).to_bytes();
+    let coin_bytes = coin.to_bytes();
+    assert_eq!(
+        verify_serialized_coin(&coin_bytes, &issuer_public_bytes),
+        Ok(())
+    );
+
+    assert_eq!(
+        verify_serialized_coin(&coin_bytes[..coin_bytes.len() - 1], &issuer_public_bytes),
+        Err(Error::DeserializationFailed)
+    );
+    assert_eq!(
+        verify_serialized_coin(&coin_bytes, &issuer_public_bytes[1..]),
+        Err(Error::DeserializationFailed)
+    );
+
+    let other_public_bytes = CoinVerifier::new(Issuer::new(params.clone()).h, params).to_bytes();
+    assert_eq!(
+        verify_serialized_coin(&coin_bytes, &other_public_bytes),
+        Err(Error::InvalidCoin(VerifyError::SignatureEqnFailed))
+    );
+}
+
+#[test]
+fn test_big_endian_coin() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let be_params = params.clone().with_endianness(Endianness::Big);
+    let decoded = Params::try_from(be_params.to_bytes().as_slice()).unwrap();
+    assert_eq!(decoded.endianness(), Endianness::Big);
+
+    let (issuer, spender) = setup_accounts(&be_params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+    assert!(coin.verify(&issuer.h, &be_params));
+
+    let challenge = CoinChallenge::new_with_endianness(b"message", &coin, Endianness::Big);
+    assert_ne!(challenge, CoinChallenge::new(b"message", &coin));
+    let challenge_bytes = challenge.to_bytes_with_endianness(Endianness::Big);
+    assert_ne!(challenge_bytes, challenge.to_bytes());
+    assert_eq!(
+        CoinChallenge::from_bytes_with_endianness(&challenge_bytes, Endianness::Big).unwrap(),
+        challenge
+    );
+
+    let spent_coin = spender.spend(coin.clone(), partial_coin, &challenge);
+    let spent_coin_bytes = spent_coin.to_bytes_with_endianness(Endianness::Big);
+    assert_ne!(spent_coin_bytes, spent_coin.to_bytes());
+    let decoded =
+        SpentCoin::from_bytes_with_endianness(&spent_coin_bytes, Endianness::Big).unwrap();
+    assert!(decoded.verify(&challenge, &be_params));
+    assert!(SpentCoin::from_bytes(&spent_coin_bytes).is_err());
+
+    let bytes = coin.to_bytes_with_endianness(Endianness::Big);
+    assert_ne!(bytes, coin.to_bytes());
+    let decoded = Coin::from_bytes_with_endianness(&bytes, Endianness::Big).unwrap();
+    assert!(decoded == coin);
+    assert!(decoded.verify(&issuer.h, &be_params));
+    assert_eq!(
+        Coin::peek_c1_with_endianness(&bytes, Endianness::Big).unwrap(),
+        Coin::peek_c1(&coin.to_bytes()).unwrap()
+    );
+
+    // The coin is hashed in a different byte order by a little-endian verifier.
+    let verifier = CoinVerifier::new(issuer.h.clone(), params);
+    assert_eq!(verifier.verify(&coin), Err(VerifyError::ChallengeMismatch));
+    // The bytes are decoded in a different byte order.
+    assert!(Coin::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_coin_peek_c1() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw_k_show(withdrawal_params, 3).unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (k_show_coin, _) = spender.make_coin(withdrawal, withdrawal_response);
+
+    for coin in [coin, k_show_coin] {
+        let bytes = coin.to_bytes();
+        let decoded = Coin::from_bytes(&bytes).unwrap();
+        let c1 = Coin::peek_c1(&bytes).unwrap();
+        assert_eq!(
+            serde_json::to_value(&c1).unwrap(),
+            serde_json::to_value(&decoded).unwrap()["c1"]
+        );
+        assert_eq!(
+            Coin::peek_c1(&bytes[..3]),
+            Err(Error::DeserializationFailed)
+        );
+    }
+}
+
+#[test]
+fn test_params_from_dh_group_canonical() {
+    let params = Params::from_dh_group_canonical::<MODPGroup5>("brandskey".to_string());
+    let params_2 = Params::from_dh_group_canonical::<MODPGroup5>("brandskey".to_string());
+    assert_eq!(params.to_bytes(), params_2.to_bytes());
+    assert!(params.validate().is_ok());
+
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+    assert!(coin.verify(&issuer.h, &params));
+}
+
+#[test]
+fn test_spent_coin_verify_many() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw_k_show(withdrawal_params, 2).unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+
+    let mut spent: Vec<(SpentCoin, CoinChallenge)> = ["shopA", "shopB"]
+        .iter()
+        .enumerate()
+        .map(|(slot, message)| {
+            let challenge = CoinChallenge::new(message.as_bytes(), &coin);
+            let spent_coin = spender
+                .spend_slot(coin.clone(), &partial_coin, slot as u32, &challenge)
+                .unwrap();
+            (spent_coin, challenge)
+        })
+        .collect();
+    assert_eq!(SpentCoin::verify_many(&spent, &params), vec![true, true]);
+
+    // The identical entry is not a double spending.
+    spent.push(spent[1].clone());
+    assert_eq!(
+        SpentCoin::verify_many(&spent, &params),
+        vec![true, true, true]
+    );
+
+    // Spends the first slot again upon the challenge of the second show.
+    let challenge = spent[1].1.clone();
+    let spent_coin = spender
+        .spend_slot(coin, &partial_coin, 0, &challenge)
+        .unwrap();
+    assert!(spent_coin.verify(&challenge, &params));
+    spent[2] = (spent_coin, challenge);
+    assert_eq!(
+        SpentCoin::verify_many(&spent, &params),
+        vec![false, true, false]
+    );
+}
+
+#[test]
+fn test_spent_coin_unreduced_responses() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let challenge = CoinChallenge::new("shopA".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+    let challenge_2 = CoinChallenge::new("shopB".as_bytes(), &coin);
+    let spent_coin_2 = spender.spend(coin, partial_coin, &challenge_2);
+
+    // Adds q to the responses, as a crafted serialization may do.
+    let q = MODPGroup5::sophie_garmain_prime();
+    let unreduce = |spent_coin: &SpentCoin| {
+        let mut value = serde_json::to_value(spent_coin).unwrap();
+        for r in ["r1", "r2"] {
+            let x: BigUint = serde_json::from_value(value[r].take()).unwrap();
+            value[r] = serde_json::to_value(x + &q).unwrap();
+        }
+        serde_json::from_value::<SpentCoin>(value).unwrap()
+    };
+    let unreduced = unreduce(&spent_coin);
+    let unreduced_2 = unreduce(&spent_coin_2);
+    assert!(unreduced.verify(&challenge, &params));
+    assert!(unreduced_2.verify(&challenge_2, &params));
+
+    assert_eq!(
+        unreduced.reveal_identity(&spent_coin_2, &params),
+        Ok(spender.i.clone())
+    );
+    assert_eq!(
+        spent_coin.reveal_identity(&unreduced_2, &params),
+        Ok(spender.i.clone())
+    );
+    assert_eq!(
+        unreduced.reveal_identity(&spent_coin, &params),
+        Err(Error::SameChallenge)
+    );
+
+    let mut ledger = DepositLedger::new(params.clone());
+    assert_eq!(
+        ledger.deposit(spent_coin, challenge.clone()),
+        Ok(DepositOutcome::Accepted)
+    );
+    assert_eq!(
+        ledger.deposit(unreduced, challenge),
+        Ok(DepositOutcome::Duplicate)
+    );
+}
+
+#[test]
+fn test_params_builder() {
+    let build = || {
+        ParamsBuilder::new()
+            .scheme_key("brandskey")
+            .group::<MODPGroup5>()
+            .deterministic_generators()
+            .validate()
+            .build()
+            .unwrap()
+    };
+    let params = build();
+    assert_eq!(params.to_bytes(), build().to_bytes());
+    assert_eq!(
+        params.to_bytes(),
+        Params::from_dh_group_canonical::<MODPGroup5>("brandskey".to_string()).to_bytes()
+    );
+
+    let params = ParamsBuilder::new()
+        .scheme_key("brandskey")
+        .group::<MODPGroup5>()
+        .random_generators()
+        .build()
+        .unwrap();
+    assert!(params.validate().is_ok());
+    assert_eq!(params.scheme_key(), "brandskey");
+
+    assert!(matches!(
+        ParamsBuilder::new().group::<MODPGroup5>().build(),
+        Err(Error::MissingSetting)
+    ));
+    assert!(matches!(
+        ParamsBuilder::new().scheme_key("brandskey").build(),
+        Err(Error::MissingSetting)
+    ));
+}
+
+#[test]
+fn test_reveal_identity_verified() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    let challenge = CoinChallenge::new("shopA".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+    let challenge_2 = CoinChallenge::new("shopB".as_bytes(), &coin);
+    let spent_coin_2 = spender.spend(coin.clone(), partial_coin, &challenge_2);
+    assert_eq!(
+        spent_coin.reveal_identity_verified(&spent_coin_2, &challenge, &challenge_2, &params),
+        Ok(spender.i.clone())
+    );
+
+    // The second spent coin does not pass its own verification upon the claimed challenge.
+    let challenge_3 = CoinChallenge::new("shopC".as_bytes(), &coin);
+    assert!(!spent_coin_2.verify(&challenge_3, &params));
+    assert_eq!(
+        spent_coin.reveal_identity_verified(&spent_coin_2, &challenge, &challenge_3, &params),
+        Err(Error::InvalidSpentCoin)
+    );
+    assert_eq!(
+        spent_coin.reveal_identity_verified(&spent_coin, &challenge, &challenge, &params),
+        Err(Error::SameChallenge)
+    );
+}
+
+#[test]
+fn test_coin_fields() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    let value = serde_json::to_value(&coin).unwrap();
+    let fields = coin.fields();
+    assert_eq!(fields.len(), Coin::FIELD_COUNT);
+    for (field, name) in fields
+        .iter()
+        .zip(["c1", "c2", "c3", "c4", "c5", "c6", "cd"])
+    {
+        assert_eq!(serde_json::to_value(field).unwrap(), value[name]);
+    }
+    assert_eq!(Coin::peek_c1(&coin.to_bytes()).unwrap(), *fields[0]);
+}
+
+#[test]
+fn test_coin_from_fields() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    let reconstructed = Coin::from_fields(coin.fields().map(Clone::clone));
+    assert!(reconstructed == coin);
+    assert!(reconstructed.verify(&issuer.h, &params));
+
+    // The fields out of order make an invalid coin.
+    let [c1, c2, c3, c4, c5, c6, cd] = coin.fields().map(Clone::clone);
+    let swapped = Coin::from_fields([c1, c2, c3, c5, c4, c6, cd]);
+    assert!(!swapped.verify(&issuer.h, &params));
+}
+
+#[test]
+fn test_register_invalid_identity() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let issuer = Issuer::new(params.clone());
+    let p = MODPGroup5::prime_modulus();
+
+    assert_eq!(issuer.register(&issuer.h), Err(Error::InvalidIdentity));
+    assert_eq!(
+        issuer.register(&BigUint::from(1u64)),
+        Err(Error::InvalidIdentity)
+    );
+    // p - 1 is of order 2, not in the subgroup of order q.
+    assert_eq!(issuer.register(&(&p - 1u64)), Err(Error::InvalidIdentity));
+    assert_eq!(issuer.register(&p), Err(Error::InvalidIdentity));
+
+    let spender = Spender::new(params);
+    assert!(issuer.register(&spender.i).is_ok());
+}
+
+#[test]
+fn test_coin_proof() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    let proof = coin.proof();
+    assert!(proof.verify(&issuer.h, &params));
+    assert!(proof.to_bytes().len() < coin.to_bytes().len());
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let metadata = CoinMetadata::new(100, b"EUR".to_vec());
+    let (withdrawal, withdrawal_challenge) = spender
+        .withdraw_with_metadata(withdrawal_params, metadata)
+        .unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin_with_metadata, _) = spender.make_coin(withdrawal, withdrawal_response);
+
+    // A coin with a field altered, and a coin of another issuer.
+    let [c1, c2, c3, c4, c5, c6, cd] = coin.fields().map(Clone::clone);
+    let altered = Coin::from_fields([c1, c2, c3, c4, c5, c6 + 1u64, cd]);
+    let other_issuer = Issuer::new(params.clone());
+
+    for (coin, h) in [
+        (&coin, &issuer.h),
+        (&coin_with_metadata, &issuer.h),
+        (&altered, &issuer.h),
+        (&coin, &other_issuer.h),
+        (&coin, &BigUint::from(1u64)),
+    ] {
+        assert_eq!(coin.proof().verify(h, &params), coin.verify(h, &params));
+    }
+}
+
+#[test]
+fn test_coin_proof_from_bytes() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender
+        .withdraw_with_metadata(withdrawal_params, CoinMetadata::new(100, b"EUR".to_vec()))
+        .unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin_with_metadata, _) = spender.make_coin(withdrawal, withdrawal_response);
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw_k_show(withdrawal_params, 3).unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (k_show_coin, _) = spender.make_coin(withdrawal, withdrawal_response);
+
+    for coin in [&coin, &coin_with_metadata, &k_show_coin] {
+        let proof = coin.proof();
+        let decoded = CoinProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(decoded == proof);
+        assert!(decoded.verify(&issuer.h, &params));
+
+        let bytes = proof.to_bytes_with_endianness(Endianness::Big);
+        assert_ne!(bytes, proof.to_bytes());
+        let decoded = CoinProof::from_bytes_with_endianness(&bytes, Endianness::Big).unwrap();
+        assert!(decoded == proof);
+    }
+
+    // Truncated, trailing and oversized bytes are rejected.
+    let bytes = coin.proof().to_bytes();
+    assert!(matches!(
+        CoinProof::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(Error::DeserializationFailed)
+    ));
+    assert!(matches!(
+        CoinProof::from_bytes(&[]),
+        Err(Error::DeserializationFailed)
+    ));
+    let mut oversized = 4097u32.to_le_bytes().to_vec();
+    oversized.extend_from_slice(&[1u8; 4097]);
+    assert!(matches!(
+        CoinProof::from_bytes(&oversized),
+        Err(Error::DeserializationFailed)
+    ));
+    let mut trailing = coin_with_metadata.proof().to_bytes();
+    trailing.push(0);
+    assert!(CoinProof::from_bytes(&trailing).is_err());
+}
+
+#[test]
+fn test_withdraw_reduced_exponent() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+
+    // bd equals b^(s * u) * A^v computed with the unreduced exponent s * u.
+    let value = serde_json::to_value(&withdrawal).unwrap();
+    let get =
+        |value: &serde_json::Value| -> BigUint { serde_json::from_value(value.clone()).unwrap() };
+    let p = MODPGroup5::prime_modulus();
+    let partial_coin = &value["partial_coin"];
+    let su = get(&partial_coin["s"]) * get(&partial_coin["u"]);
+    assert!(su > MODPGroup5::sophie_garmain_prime());
+    let bd = (get(&value["b_by_issuer"]).modpow(&su, &p)
+        * get(&value["a"]).modpow(&get(&partial_coin["v"]), &p))
+        % &p;
+    assert_eq!(bd, get(&value["bd"]));
+
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, _) = spender.make_coin(withdrawal, withdrawal_response);
+    assert!(coin.verify(&issuer.h, &params));
+}
+
+#[test]
+fn test_coin_research_record() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    let record = coin.to_research_record();
+    assert_eq!(record, coin.to_research_record());
+    assert_eq!(record.fingerprint.len(), 64);
+    assert_eq!(record.c1_bits, coin.fields()[0].bits());
+    assert_eq!(record.shows, 1);
+    assert!(!record.has_metadata);
+
+    // None of the values of the fields appears in the record, in decimal or in hex.
+    let json = serde_json::to_string(&record).unwrap();
+    for field in coin.fields() {
+        assert!(!json.contains(&field.to_string()));
+        assert!(!json.contains(&field.to_str_radix(16)));
+    }
+    assert_eq!(
+        serde_json::from_str::<ResearchRecord>(&json).unwrap(),
+        record
+    );
+    let value = serde_json::to_value(&record).unwrap();
+    assert!(value
+        .as_object()
+        .unwrap()
+        .values()
+        .all(|v| !v.is_array() && !v.is_object()));
+}
+
+#[test]
+fn test_settlement() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+    let mut settlement = Settlement::new(issuer);
+    let h = settlement.issuer().h.clone();
+
+    // Accepted, with a receipt of the issuer.
+    let challenge = CoinChallenge::new("shopA".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+    let receipt = match settlement
+        .settle(spent_coin.clone(), challenge.clone())
+        .unwrap()
+    {
+        SettlementResult::Accepted(receipt) => receipt,
+        result => panic!("unexpected result: {:?}", result),
+    };
+    assert_eq!(receipt.fingerprint, coin.fingerprint());
+    assert!(receipt.verify(&h, &params));
+
+    // Duplicate.
+    assert_eq!(
+        settlement.settle(spent_coin, challenge).unwrap(),
+        SettlementResult::Duplicate
+    );
+
+    // Double spend, which reveals the identity of the spender.
+    let challenge_2 = CoinChallenge::new("shopB".as_bytes(), &coin);
+    let spent_coin_2 = spender.spend(coin.clone(), partial_coin, &challenge_2);
+    match settlement
+        .settle(spent_coin_2.clone(), challenge_2.clone())
+        .unwrap()
+    {
+        SettlementResult::DoubleSpend(event) => {
+            assert_eq!(event.identity, spender.i);
+            assert_eq!(event.fingerprint, coin.fingerprint());
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    // Rejected, if the spent coin is not valid upon the challenge.
+    assert!(matches!(
+        settlement.settle(spent_coin_2, CoinChallenge::new("shopC".as_bytes(), &coin)),
+        Err(Error::InvalidCoinInGroup { .. })
+    ));
+}
+
+#[test]
+fn test_params_label() {
+    let params = Params::from_dh_group_canonical::<MODPGroup5>("brandskey".to_string());
+    assert_eq!(params.label(), Some("MODPGroup5"));
+    assert!(format!("{:?}", params).contains("MODPGroup5"));
+
+    let params = params.with_label("test-group");
+    let serialized = serde_json::to_string(&params).unwrap();
+    let deserialized: Params = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.label(), Some("test-group"));
+
+    // The label appears in the error of a coin failing verification.
+    let (issuer, spender) = setup_accounts(&deserialized);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+    let other_issuer = Issuer::new(deserialized.clone());
+    let challenge = CoinChallenge::new("shopA".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin.clone(), partial_coin, &challenge);
+    let err = Settlement::new(other_issuer)
+        .settle(spent_coin, challenge)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Error::InvalidCoinInGroup {
+            label: "test-group".to_string(),
+            reason: VerifyError::SignatureEqnFailed,
+        }
+    );
+    assert!(err.to_string().contains("test-group"));
+
+    // The label is neither encoded in the bytes nor a part of the hash of a coin.
+    let unlabelled = Params::try_from(deserialized.to_bytes().as_slice()).unwrap();
+    assert_eq!(unlabelled.label(), None);
+    assert!(coin.verify(&issuer.h, &unlabelled));
+}
+
+#[test]
+fn test_issuer_key_commitment() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let issuer = Issuer::new(params.clone());
+    let commitment = issuer.key_commitment();
+    assert_eq!(commitment, issuer.key_commitment());
+
+    let verifier = CoinVerifier::new(issuer.h.clone(), params.clone());
+    assert!(verifier.check_issuer_commitment(&commitment));
+
+    // A tampered h fails the check.
+    let other_issuer = Issuer::new(params.clone());
+    let tampered = CoinVerifier::new(other_issuer.h.clone(), params.clone());
+    assert!(!tampered.check_issuer_commitment(&commitment));
+    let tampered = CoinVerifier::new(&issuer.h + 1u64, params.clone());
+    assert!(!tampered.check_issuer_commitment(&commitment));
+
+    // The commitment binds the parameters as well.
+    let tampered = CoinVerifier::new(issuer.h.clone(), params.with_tenant_id(b"tenant"));
+    assert!(!tampered.check_issuer_commitment(&commitment));
+}
+
+#[test]
+fn test_issuer_key_ring() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (old_issuer, spender) = setup_accounts(&params);
+    let (old_coin, _) = withdraw_coin(&old_issuer, &spender);
+
+    // The issuer rotates its key.
+    let new_issuer = Issuer::new(params.clone());
+    let mut spender = Spender::new(params.clone());
+    spender.set_registration_id(new_issuer.register(&spender.i).unwrap());
+    let (new_coin, _) = withdraw_coin(&new_issuer, &spender);
+
+    let mut ring = IssuerKeyRing::new(params.clone());
+    assert_eq!(ring.verify(&old_coin), Err(VerifyError::InvalidIssuerKey));
+    ring.insert(1, old_issuer.h.clone()).unwrap();
+    ring.insert(2, new_issuer.h.clone()).unwrap();
+    assert_eq!(ring.verify(&old_coin), Ok(1));
+    assert_eq!(ring.verify(&new_coin), Ok(2));
+    assert_eq!(ring.verify_with(1, &old_coin), Ok(()));
+    assert!(ring.verify_with(2, &old_coin).is_err());
+    assert_eq!(
+        ring.verify_with(3, &old_coin),
+        Err(VerifyError::InvalidIssuerKey)
+    );
+    assert!(VerifyCoin::verify(&ring, &new_coin.to_bytes()).is_ok());
+
+    // A coin of another issuer is rejected.
+    let (other_issuer, spender) = setup_accounts(&params);
+    let (other_coin, _) = withdraw_coin(&other_issuer, &spender);
+    assert!(ring.verify(&other_coin).is_err());
+
+    // The coins under the retired key are no longer accepted.
+    assert_eq!(ring.remove(1), Some(old_issuer.h.clone()));
+    assert!(ring.verify(&old_coin).is_err());
+    assert_eq!(ring.verify(&new_coin), Ok(2));
+    assert_eq!(
+        ring.insert(3, BigUint::from(1u64)),
+        Err(VerifyError::InvalidIssuerKey)
+    );
+}
+
+#[test]
+fn test_params_security_bits() {
+    let params = Params::from_dh_group_canonical::<MODPGroup5>("brandskey".to_string());
+    assert_eq!(params.security_bits(), 80);
+
+    // p = 23 = 2 * 11 + 1
+    let params = Params::from_str("brandskey".to_string(), "23", "11", "4", "9", "3").unwrap();
+    assert!(params.security_bits() < 8);
+
+    // The subgroup bounds the estimate if it is much smaller than the group.
+    let params = Params::from_str(
+        "brandskey".to_string(),
+        &MODPGroup5::prime_modulus().to_string(),
+        "1000003",
+        "4",
+        "9",
+        "25",
+    )
+    .unwrap();
+    assert_eq!(params.security_bits(), 10);
+}
+
+#[test]
+fn test_coin_challenge_new_batch() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let coins: Vec<Coin> = (0..3).map(|_| withdraw_coin(&issuer, &spender).0).collect();
+
+    let message = "shopA-cart-1718193570".as_bytes();
+    let challenges = CoinChallenge::new_batch(message, &coins);
+    assert_eq!(challenges.len(), coins.len());
+    for (challenge, coin) in challenges.iter().zip(&coins) {
+        assert_eq!(*challenge, CoinChallenge::new(message, coin));
+    }
+    // Each challenge binds its own coin.
+    assert_ne!(challenges[0], challenges[1]);
+    assert!(CoinChallenge::new_batch(message, &[]).is_empty());
+}
+
+#[test]
+fn test_params_generators_have_order_q() {
+    let p = MODPGroup5::prime_modulus();
+    let q = MODPGroup5::sophie_garmain_prime();
+    for _ in 0..10 {
+        let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+        assert!(params.validate().is_ok());
+    }
+    let params = Params::from_dh_group_canonical::<MODPGroup5>("brandskey".to_string());
+    assert!(params.validate().is_ok());
+
+    // 1 and p - 1 (of order 2) are rejected.
+    for g in [BigUint::from(1u64), &p - 1u64] {
+        assert!(!brands::math::is_subgroup_generator(&g, &q, &p));
+        assert_eq!(
+            Params::from_dh_group_with_generators::<MODPGroup5>(
+                "brandskey".to_string(),
+                g,
+                BigUint::from(9u64),
+                BigUint::from(25u64),
+            )
+            .err(),
+            Some(Error::InvalidGenerator)
+        );
+    }
+}
+
+#[test]
+fn test_interactive_spend() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, partial_coin) = withdraw_coin(&issuer, &spender);
+
+    // The spender offers the coin, and the receiver responds with a fresh random challenge.
+    assert!(coin.verify(&issuer.h, &params));
+    let challenge = Receiver::challenge(&coin);
+    assert_ne!(challenge, Receiver::challenge(&coin));
+    assert_ne!(challenge, CoinChallenge::new(&[], &coin));
+
+    let spent_coin = spender.spend(coin.clone(), partial_coin.clone(), &challenge);
+    assert!(spent_coin.verify(&challenge, &params));
+    assert!(!spent_coin.verify(&Receiver::challenge(&coin), &params));
+
+    // Spending the coin again upon another random challenge reveals the spender.
+    let challenge_2 = Receiver::challenge(&coin);
+    let spent_coin_2 = spender.spend(coin, partial_coin, &challenge_2);
+    assert!(spent_coin_2.verify(&challenge_2, &params));
+    assert_eq!(
+        spent_coin.reveal_identity(&spent_coin_2, &params),
+        Ok(spender.i.clone())
+    );
+}
+
+#[test]
+fn test_params_from_str_validated() {
+    let p = MODPGroup5::prime_modulus().to_string();
+    let q = MODPGroup5::sophie_garmain_prime().to_string();
+    let params = Params::from_str_validated("brandskey".to_string(), &p, &q, "4", "9", "25");
+    assert!(params.is_ok());
+
+    // p - 1 is not in the subgroup of order q.
+    let g = (MODPGroup5::prime_modulus() - 1u64).to_string();
+    assert!(Params::from_str("brandskey".to_string(), &p, &q, &g, "9", "25").is_some());
+    assert_eq!(
+        Params::from_str_validated("brandskey".to_string(), &p, &q, &g, "9", "25").err(),
+        Some(Error::InvalidGenerator)
+    );
+    assert_eq!(
+        Params::from_str_validated("brandskey".to_string(), &p, &q, "4x", "9", "25").err(),
+        Some(Error::DeserializationFailed)
+    );
+    assert_eq!(
+        Params::from_str_validated("brandskey".to_string(), &p, "11", "4", "9", "25").err(),
+        Some(Error::InvalidParams)
+    );
+}
+
+#[test]
+fn test_coin_bound_pseudonym() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let pseudonym = BigUint::from(0x5eed_1234u64);
+
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender
+        .withdraw_with_metadata(
+            withdrawal_params,
+            CoinMetadata::with_pseudonym(100, &pseudonym),
+        )
+        .unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, _) = spender.make_coin(withdrawal, withdrawal_response);
+    assert!(coin.verify(&issuer.h, &params));
+    assert_eq!(coin.bound_pseudonym(), Some(pseudonym));
+
+    // Neither a coin without metadata nor one with other attributes carries a pseudonym.
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+    assert_eq!(coin.bound_pseudonym(), None);
+    assert_eq!(
+        CoinMetadata::new(100, b"brands-rs/pseudonym".to_vec()).pseudonym(),
+        None
+    );
+    assert_eq!(CoinMetadata::new(100, vec![1, 2, 3]).pseudonym(), None);
+}
+
+#[test]
+fn test_coin_field_tamper_resistance() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+    let verifier = CoinVerifier::new(issuer.h.clone(), params.clone());
+    assert_eq!(verifier.verify(&coin), Ok(()));
+
+    // The fields in the order of Coin::fields, with the error of the first check failing when
+    // the field is perturbed.
+    let expected = [
+        // c1, c2, c3, c4 and c5 are bound by the hash cd = Hash(c1, c2, c3, c4, c5).
+        ("c1", VerifyError::ChallengeMismatch),
+        ("c2", VerifyError::ChallengeMismatch),
+        ("c3", VerifyError::ChallengeMismatch),
+        ("c4", VerifyError::ChallengeMismatch),
+        ("c5", VerifyError::ChallengeMismatch),
+        // c6 is bound by the equations c4 * h^cd = g^c6 and c5 * c3^cd = c1^c6.
+        ("c6", VerifyError::SignatureEqnFailed),
+        // cd is the hash itself.
+        ("cd", VerifyError::ChallengeMismatch),
+    ];
+    assert_eq!(expected.len(), Coin::FIELD_COUNT);
+    for (index, (name, err)) in expected.into_iter().enumerate() {
+        let mut fields = coin.fields().map(Clone::clone);
+        fields[index] += 1u64;
+        let tampered = Coin::from_fields(fields);
+        assert!(
+            !tampered.verify(&issuer.h, &params),
+            "{} is not bound",
+            name
+        );
+        assert_eq!(verifier.verify(&tampered), Err(err), "{}", name);
+    }
+}
+
+#[test]
+fn test_withdrawal_recompute_challenge() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+
+    let (withdrawal_params, _) = issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+    assert!(withdrawal.recompute_challenge(&params).unwrap() == withdrawal_challenge);
+
+    let (withdrawal_params, _) = issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender
+        .withdraw_with_metadata(withdrawal_params, CoinMetadata::new(100, vec![1]))
+        .unwrap();
+    assert!(withdrawal.recompute_challenge(&params).unwrap() == withdrawal_challenge);
+
+    // A persisted withdrawal which is corrupted recomputes another challenge.
+    let mut serialized = serde_json::to_value(&withdrawal).unwrap();
+    serialized["metadata"]["denomination"] = 1000.into();
+    let corrupted: Withdrawal = serde_json::from_value(serialized).unwrap();
+    assert!(corrupted.recompute_challenge(&params).unwrap() != withdrawal_challenge);
+}
+
+#[test]
+fn test_coin_challenge_new_checked() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+
+    assert_eq!(
+        CoinChallenge::new_checked(&[], &coin),
+        Err(Error::EmptyMessage)
+    );
+    let message = "shopA-payment-item-1718193570".as_bytes();
+    assert_eq!(
+        CoinChallenge::new_checked(message, &coin),
+        Ok(CoinChallenge::new(message, &coin))
+    );
+}
+
+/// A random number generator supplied by the host (e.g. a JS-backed one in WASM), which counts
+/// the bytes drawn from it.
+struct HostRng {
+    inner: StdRng,
+    drawn: usize,
+}
+
+impl rand::RngCore for HostRng {
+    fn next_u32(&mut self) -> u32 {
+        self.drawn += 4;
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.drawn += 8;
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.drawn += dest.len();
+        self.inner.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_supplied_rng() {
+    let params = Params::from_dh_group_canonical::<MODPGroup5>("brandskey".to_string());
+    let host_rng = |seed: u64| HostRng {
+        inner: StdRng::seed_from_u64(seed),
+        drawn: 0,
+    };
+
+    let mut rng = host_rng(1);
+    let dyn_rng: &mut dyn rand::RngCore = &mut rng;
+    let issuer = Issuer::new_with_rng(params.clone(), dyn_rng);
+    let mut spender = Spender::new_with_rng(params.clone(), dyn_rng);
+    spender.set_registration_id(issuer.register(&spender.i).unwrap());
+    let (withdrawal_params, withdrawal_response_params) = issuer
+        .setup_withdrawal_params_many(&spender.i, 1, dyn_rng)
+        .pop()
+        .unwrap();
+    let (withdrawal, withdrawal_challenge) = spender
+        .withdraw_with_rng(withdrawal_params.clone(), dyn_rng)
+        .unwrap();
+    assert!(rng.drawn > 0);
+
+    // The secrets are sampled only from the supplied generator.
+    let mut rng_2 = host_rng(1);
+    let issuer_2 = Issuer::new_with_rng(params.clone(), &mut rng_2);
+    let spender_2 = Spender::new_with_rng(params.clone(), &mut rng_2);
+    assert_eq!(issuer_2.h, issuer.h);
+    assert_eq!(spender_2.i, spender.i);
+
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+    assert!(coin.verify(&issuer.h, &params));
+    let challenge = CoinChallenge::new("shopA".as_bytes(), &coin);
+    let spent_coin = spender.spend(coin, partial_coin, &challenge);
+    assert!(spent_coin.verify(&challenge, &params));
+
+    // Every random value of the flows is drawn from the supplied generator, so that the flows
+    // are reproduced from the same seed.
+    let (coins, signature, receipts) = supplied_rng_flows(&params, 2);
+    assert_eq!(coins.len(), 3);
+    assert_eq!(coins[1].shows(), 3);
+    assert_eq!(receipts[0].amount, 100);
+    let (coins_2, signature_2, receipts_2) = supplied_rng_flows(&params, 2);
+    assert!(coins == coins_2);
+    assert_eq!(signature_2, signature);
+    assert_eq!(receipts_2, receipts);
+}
+
+/// Runs the withdrawals (plain, k-show, with metadata and refresh), the signing of a transaction
+/// and the receipts with all of the randomness drawn from a generator seeded by `seed`.
+fn supplied_rng_flows(
+    params: &Params,
+    seed: u64,
+) -> (Vec<Coin>, TransactionSignature, Vec<Receipt>) {
+    let mut rng = HostRng {
+        inner: StdRng::seed_from_u64(seed),
+        drawn: 0,
+    };
+    let rng: &mut dyn rand::RngCore = &mut rng;
+    let issuer = Issuer::new_with_rng(params.clone(), rng);
+    let mut spender = Spender::new_with_rng(params.clone(), rng);
+    spender.set_registration_id(issuer.register(&spender.i).unwrap());
+
+    let mut coins = Vec::new();
+    let mut partial_coins = Vec::new();
+    for kind in 0..3 {
+        let (withdrawal_params, withdrawal_response_params) =
+            issuer.setup_withdrawal_params_with_rng(&spender.i, rng);
+        let (withdrawal, withdrawal_challenge) = match kind {
+            0 => spender.withdraw_with_rng(withdrawal_params, rng),
+            1 => spender.withdraw_k_show_with_rng(withdrawal_params, 3, rng),
+            _ => spender.withdraw_with_metadata_with_rng(
+                withdrawal_params,
+                CoinMetadata::new(100, b"EUR".to_vec()),
+                rng,
+            ),
+        }
+        .unwrap();
+        let withdrawal_response = issuer
+            .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+            .unwrap();
+        let (coin, partial_coin) = spender.make_coin(withdrawal, withdrawal_response);
+        assert!(coin.verify(&issuer.h, params));
+        coins.push(coin);
+        partial_coins.push(partial_coin);
+    }
+
+    let transaction = b"pay 5 EUR to shopA";
+    let signature = spender.sign_transaction_with_rng(&partial_coins[0], transaction, rng);
+    assert!(signature.verify(
+        &spender.onetime_public_key(&partial_coins[0]),
+        transaction,
+        params
+    ));
+
+    let challenge = Receiver::challenge_with_rng(&coins[2], rng);
+    let spent_coin = spender.spend(coins[2].clone(), partial_coins[2].clone(), &challenge);
+    let receipt = issuer
+        .issue_receipt_with_rng(&spent_coin, &challenge, rng)
+        .unwrap();
+    assert!(receipt.verify(&issuer.h, params));
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params_with_rng(&spender.i, rng);
+    let (withdrawal, request) = spender
+        .request_refresh_with_rng(
+            coins[0].clone(),
+            partial_coins[0].clone(),
+            withdrawal_params,
+            rng,
+        )
+        .unwrap();
+    let mut ledger = DepositLedger::new(params.clone());
+    let withdrawal_response = issuer
+        .refresh(&request, &mut ledger, withdrawal_response_params)
+        .unwrap();
+    let (refreshed, _) = spender.make_coin(withdrawal, withdrawal_response);
+    assert!(refreshed.verify(&issuer.h, params));
+    coins[0] = refreshed;
+
+    let mut settlement = Settlement::new(issuer);
+    let settled = match settlement
+        .settle_with_rng(spent_coin, challenge, rng)
+        .unwrap()
+    {
+        SettlementResult::Accepted(receipt) => receipt,
+        result => panic!("unexpected settlement result: {:?}", result),
+    };
+
+    (coins, signature, vec![receipt, settled])
+}
+
+/// A random number generator which yields only zeros, e.g. a broken generator of a host.
+struct ZeroRng;
+
+impl rand::RngCore for ZeroRng {
+    fn next_u32(&mut self) -> u32 {
+        0
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill(0)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        dest.fill(0);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_withdraw_zero_secrets() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+
+    // The secret values of the coin are never sampled as zero, even from a generator which
+    // yields only zeros.
+    let (withdrawal_params, withdrawal_response_params) =
+        issuer.setup_withdrawal_params(&spender.i);
+    let (withdrawal, withdrawal_challenge) = spender
+        .withdraw_with_rng(withdrawal_params, &mut ZeroRng)
+        .unwrap();
+    let withdrawal_response = issuer
+        .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+        .unwrap();
+    let mut serialized = serde_json::to_value(&withdrawal).unwrap();
+    let (coin, _) = spender.make_coin(withdrawal, withdrawal_response);
+    assert!(coin.verify(&issuer.h, &params));
+
+    // A withdrawal whose u is corrupted to be 0 is handled without a panic.
+    serialized["partial_coin"]["u"] = serde_json::json!([]);
+    let corrupted: Withdrawal = serde_json::from_value(serialized).unwrap();
+    assert!(matches!(
+        corrupted.recompute_challenge(&params),
+        Err(Error::NonInvertible)
+    ));
+}
+
+#[test]
+fn test_coin_structural_warnings() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+    let (coin, _) = withdraw_coin(&issuer, &spender);
+    assert_eq!(coin.structural_warnings(&params), vec![]);
+
+    let mut fields = coin.fields().map(Clone::clone);
+    fields[0] = BigUint::from(4u64);
+    let tampered = Coin::from_fields(fields);
+    assert_eq!(
+        tampered.structural_warnings(&params),
+        vec![CoinWarning::ShortField {
+            field: "c1",
+            bits: 3,
+            expected_bits: MODPGroup5::prime_modulus().bits(),
+        }]
+    );
+}
+
+#[test]
+fn test_coin_with_secrets_serde() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, spender) = setup_accounts(&params);
+
+    let coins: Vec<CoinWithSecrets> = (0..2)
+        .map(|_| {
+            let (withdrawal_params, withdrawal_response_params) =
+                issuer.setup_withdrawal_params(&spender.i);
+            let (withdrawal, withdrawal_challenge) = spender.withdraw(withdrawal_params).unwrap();
+            let withdrawal_response = issuer
+                .withdrawal_response(withdrawal_response_params, &withdrawal_challenge)
+                .unwrap();
+            spender.make_coin_with_secrets(withdrawal, withdrawal_response)
+        })
+        .collect();
+    let serialized = serde_json::to_string(&coins).unwrap();
+    let restored: Vec<CoinWithSecrets> = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(restored.len(), coins.len());
+
+    // The restored coins can still be spent.
+    for coin_with_secrets in restored {
+        let challenge = CoinChallenge::new("shopA".as_bytes(), &coin_with_secrets.coin);
+        let spent_coin = spender.spend_with_secrets(coin_with_secrets, &challenge);
+        assert!(spent_coin
+            .verify_full(&issuer.h, &challenge, &params)
+            .is_ok());
+    }
+}
+
+#[test]
+fn test_params_is_quadratic_residue() {
+    let params = Params::from_dh_group::<MODPGroup5>("brandskey".to_string());
+    let (issuer, _) = setup_accounts(&params);
+    let p = MODPGroup5::prime_modulus();
+    let q = MODPGroup5::sophie_garmain_prime();
+    let one = BigUint::from(1u64);
+    let p_minus_one = &p - &one;
+
+    let mut rng = StdRng::seed_from_u64(414);
+    let mut xs = vec![
+        BigUint::ZERO,
+        one.clone(),
+        p_minus_one.clone(),
+        p.clone(),
+        BigUint::from(4u64),
+        issuer.h.clone(),
+    ];
+    xs.extend((0..200).map(|_| rng.gen_biguint_below(&p)));
+    for x in &xs {
+        assert_eq!(
+            params.is_quadratic_residue(x),
+            brands::math::is_subgroup_element(x, &q, &p)
+        );
+        assert_eq!(
+            params.is_subgroup_element(x),
+            params.is_quadratic_residue(x)
+        );
+    }
+    assert!(params.is_quadratic_residue(&one));
+    assert!(!params.is_quadratic_residue(&p_minus_one));
+    assert!(!params.is_quadratic_residue(&p));
+}